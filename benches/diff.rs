@@ -0,0 +1,111 @@
+use assert_json_diff::{
+    assert_json_matches_no_panic, diff_json, json_matches, CompareMode, Config,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// An object nested `depth` levels deep, alternating between objects and arrays, with `leaf` at
+/// the bottom.
+fn deep(depth: usize, leaf: Value) -> Value {
+    (0..depth).fold(leaf, |value, level| {
+        if level % 2 == 0 {
+            json!({ format!("level_{}", level): value, "sibling": level })
+        } else {
+            json!([level, value])
+        }
+    })
+}
+
+/// An array of `width` objects, one of which has `leaf` as its value.
+fn wide(width: usize, leaf: Value) -> Value {
+    let users = (0..width)
+        .map(|idx| {
+            let mut user = Map::new();
+            user.insert("id".to_string(), json!(idx));
+            user.insert("name".to_string(), json!(format!("user {}", idx)));
+            user.insert("tags".to_string(), json!(["a", "b", "c"]));
+            if idx == width / 2 {
+                user.insert("value".to_string(), leaf.clone());
+            }
+            Value::Object(user)
+        })
+        .collect::<Vec<_>>();
+    json!({ "data": { "users": users } })
+}
+
+fn bench_documents(c: &mut Criterion, name: &str, documents: &[(usize, Value, Value)]) {
+    let config = Config::new(CompareMode::Strict);
+    let mut group = c.benchmark_group(name);
+
+    for (size, lhs, rhs) in documents {
+        group.bench_with_input(BenchmarkId::new("diff_json", size), size, |b, _| {
+            b.iter(|| diff_json(black_box(lhs), black_box(rhs), &config))
+        });
+        group.bench_with_input(BenchmarkId::new("json_matches", size), size, |b, _| {
+            b.iter(|| json_matches(black_box(lhs), black_box(lhs), &config))
+        });
+    }
+
+    group.finish();
+}
+
+fn deep_documents(c: &mut Criterion) {
+    let documents = [100, 1_000]
+        .iter()
+        .map(|depth| (*depth, deep(*depth, json!(1)), deep(*depth, json!(2))))
+        .collect::<Vec<_>>();
+    bench_documents(c, "deep", &documents);
+}
+
+fn wide_documents(c: &mut Criterion) {
+    let documents = [1_000, 10_000]
+        .iter()
+        .map(|width| (*width, wide(*width, json!(1)), wide(*width, json!(2))))
+        .collect::<Vec<_>>();
+    bench_documents(c, "wide", &documents);
+}
+
+#[derive(Serialize)]
+struct Fixture {
+    id: u32,
+    rows: Vec<Row>,
+}
+
+#[derive(Serialize)]
+struct Row {
+    name: String,
+    values: Vec<f64>,
+}
+
+/// Checking a small part of a large value, with and without guided serialization.
+fn serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialization");
+    let expected = json!({ "id": 1, "rows": [{ "name": "row 0" }] });
+
+    for size in [100, 1_000] {
+        let fixture = Fixture {
+            id: 1,
+            rows: (0..size)
+                .map(|idx| Row {
+                    name: format!("row {}", idx),
+                    values: vec![idx as f64; 100],
+                })
+                .collect(),
+        };
+
+        group.bench_with_input(BenchmarkId::new("full", size), &size, |b, _| {
+            let config = Config::new(CompareMode::Inclusive);
+            b.iter(|| assert_json_matches_no_panic(black_box(&fixture), &expected, config.clone()))
+        });
+        group.bench_with_input(BenchmarkId::new("guided", size), &size, |b, _| {
+            let config = Config::new(CompareMode::Inclusive).guided_serialization();
+            b.iter(|| assert_json_matches_no_panic(black_box(&fixture), &expected, config.clone()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, deep_documents, wide_documents, serialization);
+criterion_main!(benches);
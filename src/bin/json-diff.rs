@@ -0,0 +1,342 @@
+//! Compare two JSON files using the same rules as `assert_json_matches`.
+//!
+//! `json-diff dir` compares the `.json` files in two directories instead.
+//!
+//! Exits with 0 if the files match, 1 if they don't, and 2 if something went wrong, such as a file
+//! not being valid JSON.
+
+use assert_json_diff::{
+    assert_json_text_matches_no_panic, diff_dirs, json_patch, ArraySortingMode, CompareMode,
+    Config, Difference, DifferenceKind, JsonDiffError, NumericMode,
+};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
+use serde_json::{json, Map, Value};
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "json-diff",
+    version,
+    about = "Compare two JSON files using the same rules as assert-json-diff",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The left hand side, or "actual" with `--inclusive`. Use `-` for stdin.
+    #[arg(required = true)]
+    lhs: Option<String>,
+
+    /// The right hand side, or "expected" with `--inclusive`. Use `-` for stdin.
+    #[arg(required = true)]
+    rhs: Option<String>,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    /// How to print the differences.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Compare the `.json` files in two directories, matching them by their relative paths.
+    Dir(DirArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct DirArgs {
+    /// The left hand side directory, or "actual" with `--inclusive`.
+    lhs: PathBuf,
+
+    /// The right hand side directory, or "expected" with `--inclusive`.
+    rhs: PathBuf,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    /// How to print the differences.
+    #[arg(long, value_enum, default_value_t = DirFormat::Text)]
+    format: DirFormat,
+}
+
+#[derive(Debug, clap::Args)]
+struct ConfigArgs {
+    /// Allow the left hand side to contain data that isn't on the right hand side.
+    #[arg(long, conflicts_with = "strict")]
+    inclusive: bool,
+
+    /// Require the two sides to be exactly equal. This is the default.
+    #[arg(long)]
+    strict: bool,
+
+    /// How to compare numbers: `strict`, `assume-float`, `absolute:<tolerance>`,
+    /// `relative:<tolerance>`, or `ulps:<tolerance>`.
+    #[arg(long, value_parser = parse_numeric_mode, default_value = "strict")]
+    numeric_mode: NumericMode,
+
+    /// How to compare arrays.
+    #[arg(long, value_enum, default_value_t = ArrayOrder::Strict)]
+    array_order: ArrayOrder,
+
+    /// Ignore values at paths matching a pattern, such as `.data.users[*].etag`. Can be repeated.
+    #[arg(long, value_name = "PATTERN")]
+    ignore_path: Vec<String>,
+
+    /// Treat strings like `"${any_uuid}"` on the right hand side as matchers.
+    #[arg(long)]
+    placeholders: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ArrayOrder {
+    Strict,
+    Ignore,
+    Subsequence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// The same message `assert_json_matches` panics with.
+    Text,
+    /// A JSON array with an object for each difference.
+    Json,
+    /// An RFC 6902 JSON Patch that turns the left hand side into the right hand side. Only
+    /// works with the default, strict, comparison.
+    Patch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DirFormat {
+    /// A report of the files that don't match, with the differences of each file.
+    Text,
+    /// A JSON object with the files on only one side and the differences of each file.
+    Json,
+}
+
+fn parse_numeric_mode(mode: &str) -> Result<NumericMode, String> {
+    let invalid = || format!("invalid tolerance in `{}`", mode);
+    // `nan`, `inf`, and negative numbers parse as floats but aren't tolerances
+    let float_tolerance = |tolerance: &str| match tolerance.parse::<f64>() {
+        Ok(tolerance) if tolerance.is_finite() && tolerance >= 0.0 => Ok(tolerance),
+        _ => Err(invalid()),
+    };
+    match mode.split_once(':') {
+        None if mode == "strict" => Ok(NumericMode::Strict),
+        None if mode == "assume-float" => Ok(NumericMode::AssumeFloat),
+        Some(("absolute", tolerance)) => {
+            Ok(NumericMode::AbsoluteTolerance(float_tolerance(tolerance)?))
+        }
+        Some(("relative", tolerance)) => {
+            Ok(NumericMode::RelativeTolerance(float_tolerance(tolerance)?))
+        }
+        Some(("ulps", tolerance)) => {
+            let tolerance = tolerance.parse().map_err(|_| invalid())?;
+            Ok(NumericMode::Ulps(tolerance))
+        }
+        _ => Err(format!("unknown numeric mode `{}`", mode)),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if args.format == Format::Patch {
+        // the patch is made from the whole values, so it can't honor a looser comparison
+        if let Some(flag) = args.config.non_strict_flag() {
+            Args::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!("`--format patch` can't be used with `{}`", flag),
+                )
+                .exit();
+        }
+    }
+    let result = match &args.command {
+        Some(Command::Dir(dir_args)) => run_dir(dir_args),
+        None => run(&args),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("json-diff: {}", error);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Compare the inputs and print the differences. Returns whether they match.
+fn run(args: &Args) -> Result<bool, String> {
+    // clap makes sure both are given when there is no subcommand
+    let (lhs, rhs) = match (&args.lhs, &args.rhs) {
+        (Some(lhs), Some(rhs)) => (lhs, rhs),
+        _ => unreachable!(),
+    };
+    if lhs == "-" && rhs == "-" {
+        return Err("only one side can be read from stdin".to_string());
+    }
+    let lhs = read(lhs)?;
+    let rhs = read(rhs)?;
+
+    let differences = match assert_json_text_matches_no_panic(&lhs, &rhs, config(&args.config)?) {
+        Ok(()) => vec![],
+        Err(JsonDiffError::Mismatch(mismatch)) => {
+            if args.format == Format::Text {
+                println!("{}", mismatch);
+                return Ok(false);
+            }
+            mismatch.into_differences()
+        }
+        Err(error) => return Err(error.to_string()),
+    };
+
+    match args.format {
+        Format::Text => {}
+        Format::Json => {
+            let differences = differences.iter().map(to_json).collect::<Vec<_>>();
+            println!("{}", to_pretty(&Value::Array(differences)));
+        }
+        Format::Patch => {
+            // both sides were parsed successfully above
+            let lhs = serde_json::from_slice(&lhs).map_err(|err| err.to_string())?;
+            let rhs = serde_json::from_slice(&rhs).map_err(|err| err.to_string())?;
+            let patch = if differences.is_empty() {
+                json!([])
+            } else {
+                json_patch(&lhs, &rhs)
+            };
+            println!("{}", to_pretty(&patch));
+        }
+    }
+
+    Ok(differences.is_empty())
+}
+
+/// Compare two directories and print the report. Returns whether they match.
+fn run_dir(args: &DirArgs) -> Result<bool, String> {
+    let report =
+        diff_dirs(&args.lhs, &args.rhs, config(&args.config)?).map_err(|err| err.to_string())?;
+
+    match args.format {
+        DirFormat::Text => {
+            if !report.is_match() {
+                println!("{}", report);
+            }
+        }
+        DirFormat::Json => {
+            let files = report
+                .failures()
+                .iter()
+                .map(|(file, error)| {
+                    let result = match error {
+                        JsonDiffError::Mismatch(mismatch) => {
+                            Value::Array(mismatch.differences().iter().map(to_json).collect())
+                        }
+                        other => json!({ "error": other.to_string() }),
+                    };
+                    (file.display().to_string(), result)
+                })
+                .collect::<Map<_, _>>();
+            let output = json!({
+                "only_in_lhs": report.only_in_lhs(),
+                "only_in_rhs": report.only_in_rhs(),
+                "files": files,
+            });
+            println!("{}", to_pretty(&output));
+        }
+    }
+
+    Ok(report.is_match())
+}
+
+impl ConfigArgs {
+    /// The first flag that makes the comparison less strict than `json_patch`, if any.
+    fn non_strict_flag(&self) -> Option<&'static str> {
+        if self.inclusive {
+            Some("--inclusive")
+        } else if self.numeric_mode != NumericMode::Strict {
+            Some("--numeric-mode")
+        } else if self.array_order != ArrayOrder::Strict {
+            Some("--array-order")
+        } else if !self.ignore_path.is_empty() {
+            Some("--ignore-path")
+        } else if self.placeholders {
+            Some("--placeholders")
+        } else {
+            None
+        }
+    }
+}
+
+fn config(args: &ConfigArgs) -> Result<Config, String> {
+    let compare_mode = if args.inclusive {
+        CompareMode::Inclusive
+    } else {
+        CompareMode::Strict
+    };
+    let array_sorting_mode = match args.array_order {
+        ArrayOrder::Strict => ArraySortingMode::Strict,
+        ArrayOrder::Ignore => ArraySortingMode::Ignore,
+        ArrayOrder::Subsequence => ArraySortingMode::Subsequence,
+    };
+
+    let mut config = Config::new(compare_mode)
+        .numeric_mode(args.numeric_mode)
+        .array_sorting_mode(array_sorting_mode);
+    for pattern in &args.ignore_path {
+        config = config
+            .try_ignore_path(pattern)
+            .map_err(|err| err.to_string())?;
+    }
+    if args.placeholders {
+        config = config.enable_placeholders();
+    }
+    Ok(config)
+}
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    if path == "-" {
+        let mut input = vec![];
+        io::stdin()
+            .read_to_end(&mut input)
+            .map_err(|err| format!("couldn't read stdin: {}", err))?;
+        Ok(input)
+    } else {
+        fs::read(path).map_err(|err| format!("couldn't read {}: {}", path, err))
+    }
+}
+
+fn to_json(difference: &Difference) -> Value {
+    let kind = match difference.kind() {
+        DifferenceKind::ValueMismatch => "value_mismatch",
+        DifferenceKind::NumberOutsideTolerance { .. } => "number_outside_tolerance",
+        DifferenceKind::TypeMismatch => "type_mismatch",
+        DifferenceKind::MatcherRejected { .. } => "matcher_rejected",
+        DifferenceKind::MissingFromLhs => "missing_from_lhs",
+        DifferenceKind::MissingFromRhs => "missing_from_rhs",
+        _ => "other",
+    };
+    let mut object = json!({
+        "path": difference.path().to_string(),
+        "kind": kind,
+        "lhs": difference.lhs(),
+        "rhs": difference.rhs(),
+        "line": difference.line(),
+    });
+    if let DifferenceKind::MatcherRejected { reason, .. } = difference.kind() {
+        object["reason"] = json!(reason);
+    }
+    object
+}
+
+fn to_pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).expect("JSON values can always be serialized")
+}
@@ -0,0 +1,215 @@
+use crate::core_ext::Indent;
+use crate::diff::{Difference, DifferenceKind, Key, Path};
+use crate::CompareMode;
+use serde_json::Value;
+
+/// Siblings longer than this are collapsed to `{...}` or `[...]`.
+const MAX_SIBLING_WIDTH: usize = 40;
+
+/// Render the ancestors of a difference, `levels` up, with the differing value marked inline.
+///
+/// The ancestors are taken from `lhs`, which is "actual" when using [`CompareMode::Inclusive`].
+/// At most `max_siblings` unchanged siblings are shown at each level, the rest are elided with
+/// `...`. Returns `None` if the difference has no ancestors.
+pub(crate) fn render_context(
+    difference: &Difference,
+    lhs: &Value,
+    levels: usize,
+    max_siblings: usize,
+) -> Option<String> {
+    let keys = difference.path().keys();
+    if keys.is_empty() || levels == 0 {
+        return None;
+    }
+
+    let (ancestor_keys, rest) = keys.split_at(keys.len().saturating_sub(levels));
+    let ancestor = ancestor_keys.iter().try_fold(lhs, |value, key| match key {
+        Key::Idx(idx) => value.get(idx),
+        Key::Field(field) => value.get(field),
+    })?;
+
+    let mut renderer = Renderer {
+        difference,
+        max_siblings,
+        lines: vec![],
+    };
+    renderer.write(0, "", Some(ancestor), rest, "");
+
+    let side = match difference.compare_mode() {
+        CompareMode::Inclusive => "actual",
+        CompareMode::Strict => "lhs",
+    };
+    let ancestor_path = ancestor_keys
+        .iter()
+        .cloned()
+        .fold(Path::default(), |path, key| path.append(key));
+
+    Some(format!(
+        "    in {} at path \"{}\":\n{}",
+        side,
+        ancestor_path,
+        renderer.lines.join("\n").indent(8)
+    ))
+}
+
+struct Renderer<'a> {
+    difference: &'a Difference,
+    max_siblings: usize,
+    lines: Vec<String>,
+}
+
+enum Item<'a> {
+    OnPath(String, Option<&'a Value>),
+    Sibling(String, &'a Value),
+    Elided,
+}
+
+impl<'a> Renderer<'a> {
+    /// Write `value`, prefixed with `label`, following `rest` down to the differing value.
+    fn write(
+        &mut self,
+        indent: usize,
+        label: &str,
+        value: Option<&'a Value>,
+        rest: &[Key],
+        comma: &str,
+    ) {
+        let pad = " ".repeat(indent);
+
+        let (key, rest) = match rest.split_first() {
+            Some(split) => split,
+            None => {
+                let value = value.map_or_else(|| "(missing)".to_string(), |v| v.to_string());
+                self.lines.push(format!(
+                    "{}{}{}{} <- {}",
+                    pad,
+                    label,
+                    value,
+                    comma,
+                    self.marker()
+                ));
+                return;
+            }
+        };
+
+        let (open, close, entries) = match value {
+            Some(Value::Object(object)) => (
+                "{",
+                "}",
+                object
+                    .iter()
+                    .map(|(k, v)| {
+                        (
+                            Key::Field(k.clone()),
+                            format!("{}: ", Value::from(k.as_str())),
+                            v,
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Some(Value::Array(array)) => (
+                "[",
+                "]",
+                array
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, v)| (Key::Idx(idx), String::new(), v))
+                    .collect(),
+            ),
+            _ => {
+                let value = value.map_or_else(|| "(missing)".to_string(), |v| v.to_string());
+                self.lines
+                    .push(format!("{}{}{}{}", pad, label, value, comma));
+                return;
+            }
+        };
+
+        // a value missing from lhs doesn't have a position, so show it at the end
+        let is_missing =
+            rest.is_empty() && self.difference.kind() == &DifferenceKind::MissingFromLhs;
+        let position = if is_missing {
+            None
+        } else {
+            entries.iter().position(|(k, _, _)| k == key)
+        };
+        let position = position.unwrap_or(entries.len());
+
+        let items = self.items(&entries, position, key);
+
+        self.lines.push(format!("{}{}{}", pad, label, open));
+        let last = items.len().saturating_sub(1);
+        for (idx, item) in items.into_iter().enumerate() {
+            let comma = if idx == last { "" } else { "," };
+            match item {
+                Item::OnPath(label, value) => self.write(indent + 2, &label, value, rest, comma),
+                Item::Sibling(label, value) => {
+                    self.lines
+                        .push(format!("{}  {}{}{}", pad, label, collapse(value), comma));
+                }
+                Item::Elided => self.lines.push(format!("{}  ...", pad)),
+            }
+        }
+        self.lines.push(format!("{}{}{}", pad, close, comma));
+    }
+
+    /// Pick the entries of a container to show, keeping the siblings closest to the path.
+    fn items<'v>(
+        &self,
+        entries: &[(Key, String, &'v Value)],
+        position: usize,
+        key: &Key,
+    ) -> Vec<Item<'v>> {
+        let mut by_distance = (0..entries.len())
+            .filter(|idx| *idx != position)
+            .collect::<Vec<_>>();
+        by_distance.sort_by_key(|idx| (*idx as isize - position as isize).abs());
+        by_distance.truncate(self.max_siblings);
+
+        let mut items = vec![];
+        for (idx, (_, label, value)) in entries.iter().enumerate() {
+            if idx == position {
+                items.push(Item::OnPath(label.clone(), Some(*value)));
+            } else if by_distance.contains(&idx) {
+                items.push(Item::Sibling(label.clone(), value));
+            } else if !matches!(items.last(), Some(Item::Elided)) {
+                items.push(Item::Elided);
+            }
+        }
+
+        if position == entries.len() {
+            let label = match key {
+                Key::Field(field) => format!("{}: ", Value::from(field.as_str())),
+                Key::Idx(_) => String::new(),
+            };
+            items.push(Item::OnPath(label, None));
+        }
+
+        items
+    }
+
+    fn marker(&self) -> String {
+        let difference = self.difference;
+        if let DifferenceKind::MatcherRejected { matcher, reason } = difference.kind() {
+            return format!("rejected by matcher \"{}\": {}", matcher, reason);
+        }
+
+        match (difference.compare_mode(), difference.rhs()) {
+            (CompareMode::Inclusive, Some(rhs)) => format!("expected {}", rhs),
+            (CompareMode::Strict, Some(rhs)) => format!("rhs is {}", rhs),
+            (_, None) => "missing from rhs".to_string(),
+        }
+    }
+}
+
+/// Show a sibling on one line, collapsing it if it is long.
+fn collapse(value: &Value) -> String {
+    let compact = value.to_string();
+    if compact.chars().count() <= MAX_SIBLING_WIDTH {
+        return compact;
+    }
+    match value {
+        Value::Object(_) => "{...}".to_string(),
+        Value::Array(_) => "[...]".to_string(),
+        _ => compact,
+    }
+}
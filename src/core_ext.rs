@@ -12,9 +12,16 @@ where
             indent.push(' ');
         }
 
+        // empty lines are left empty so there is no trailing whitespace
         self.to_string()
             .lines()
-            .map(|line| format!("{}{}", indent, line))
+            .map(|line| {
+                if line.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}{}", indent, line)
+                }
+            })
             .collect::<Vec<_>>()
             .join("\n")
     }
@@ -24,7 +31,7 @@ pub trait Indexes {
     fn indexes(&self) -> Vec<usize>;
 }
 
-impl<T> Indexes for Vec<T> {
+impl<T> Indexes for [T] {
     fn indexes(&self) -> Vec<usize> {
         if self.is_empty() {
             vec![]
@@ -42,6 +49,7 @@ mod tests {
     fn test_indent() {
         assert_eq!("  foo", "foo".indent(2));
         assert_eq!("  foo\n  bar", "foo\nbar".indent(2));
+        assert_eq!("  foo\n\n  bar", "foo\n\nbar".indent(2));
     }
 
     #[test]
@@ -50,6 +58,6 @@ mod tests {
         let empty_indexes: Vec<usize> = vec![];
         assert_eq!(empty.indexes(), empty_indexes);
 
-        assert_eq!(vec!['a', 'b'].indexes(), vec![0, 1]);
+        assert_eq!(['a', 'b'].indexes(), vec![0, 1]);
     }
 }
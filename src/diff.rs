@@ -1,21 +1,179 @@
 use crate::core_ext::{Indent, Indexes};
-use crate::{CompareMode, Config, NumericMode};
+use crate::placeholder::Placeholder;
+use crate::{ArraySortingMode, CompareMode, Config, Matcher, NumericMode};
 use serde_json::Value;
-use std::{collections::HashSet, fmt};
+use std::{convert::TryFrom, fmt};
 
-pub(crate) fn diff<'a>(lhs: &'a Value, rhs: &'a Value, config: Config) -> Vec<Difference<'a>> {
-    let mut acc = vec![];
-    diff_with(lhs, rhs, config, Path::Root, &mut acc);
+pub(crate) fn diff(lhs: &Value, rhs: &Value, config: &Config) -> Vec<Difference> {
+    diff_limited(lhs, rhs, config).found
+}
+
+/// Like [`diff`] but also counts the differences beyond `Config::max_differences`.
+pub(crate) fn diff_limited(lhs: &Value, rhs: &Value, config: &Config) -> Differences {
+    let mut acc = Differences::new(config.max_differences);
+    diff_with(lhs, rhs, config, &mut PathStack::default(), &mut acc);
     acc
 }
 
+/// Whether `lhs` and `rhs` match, stopping at the first difference.
+pub(crate) fn matches(lhs: &Value, rhs: &Value, config: &Config) -> bool {
+    let mut acc = Differences::fail_fast();
+    diff_with(lhs, rhs, config, &mut PathStack::default(), &mut acc);
+    !acc.is_done()
+}
+
+/// The differences found so far.
+///
+/// Once `limit` differences have been found the rest are only counted, so their values aren't
+/// cloned. A fail fast collector doesn't record anything, it only remembers that a difference
+/// was found so the traversal can stop.
+#[derive(Debug)]
+pub(crate) struct Differences {
+    pub(crate) found: Vec<Difference>,
+    limit: Option<usize>,
+    /// The number of omitted differences, grouped by the path prefix they share.
+    pub(crate) omitted: Vec<OmittedGroup>,
+    fail_fast: bool,
+    failed: bool,
+}
+
+impl Differences {
+    fn new(limit: Option<usize>) -> Self {
+        Self {
+            found: vec![],
+            limit,
+            omitted: vec![],
+            fail_fast: false,
+            failed: false,
+        }
+    }
+
+    fn fail_fast() -> Self {
+        Self {
+            fail_fast: true,
+            ..Self::new(Some(0))
+        }
+    }
+
+    /// A collector of the same kind, used for comparing array elements.
+    fn nested(&self) -> Self {
+        if self.fail_fast {
+            Self::fail_fast()
+        } else {
+            Self::new(None)
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.found.len() + self.omitted_count() + usize::from(self.failed)
+    }
+
+    /// Whether the traversal can stop because nothing more will be recorded.
+    fn is_done(&self) -> bool {
+        self.failed
+    }
+
+    pub(crate) fn omitted_count(&self) -> usize {
+        self.omitted.iter().map(|group| group.count).sum()
+    }
+
+    fn is_full(&self) -> bool {
+        self.limit.is_some_and(|limit| self.found.len() >= limit)
+    }
+
+    fn omit(&mut self, path: &PathStack<'_>) {
+        if self.fail_fast {
+            self.failed = true;
+            return;
+        }
+        let keys = path.keys();
+        match self.omitted.iter_mut().find(|group| group.contains(keys)) {
+            Some(group) => group.add(keys),
+            None => self.omitted.push(OmittedGroup::new(keys)),
+        }
+    }
+}
+
+/// Omitted differences under the same top level field, or in the same top level array.
+///
+/// Only the longest path prefix they share is kept, with `None` for array indices that differ
+/// between them, so `.items[3].price` and `.items[9].price` are summarized as being under
+/// `.items[*].price`.
+#[derive(Debug)]
+pub(crate) struct OmittedGroup {
+    prefix: Vec<Option<Key>>,
+    pub(crate) count: usize,
+}
+
+impl OmittedGroup {
+    fn new(keys: &[KeyRef<'_>]) -> Self {
+        Self {
+            prefix: keys.iter().map(|key| Some(key.into_key())).collect(),
+            count: 1,
+        }
+    }
+
+    fn contains(&self, keys: &[KeyRef<'_>]) -> bool {
+        match (self.prefix.first(), keys.first()) {
+            (Some(Some(Key::Field(group))), Some(KeyRef::Field(field))) => group == field,
+            (Some(Some(Key::Idx(_)) | None), Some(KeyRef::Idx(_))) => true,
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn add(&mut self, keys: &[KeyRef<'_>]) {
+        self.count += 1;
+        let mut shared = 0;
+        for (prefix, key) in self.prefix.iter_mut().zip(keys) {
+            match (&*prefix, key) {
+                (Some(Key::Field(prefix)), KeyRef::Field(field)) if prefix == field => {}
+                (Some(Key::Idx(prefix)), KeyRef::Idx(idx)) if prefix == idx => {}
+                (Some(Key::Idx(_)) | None, KeyRef::Idx(_)) => *prefix = None,
+                _ => break,
+            }
+            shared += 1;
+        }
+        self.prefix.truncate(shared);
+    }
+
+    pub(crate) fn is_root(&self) -> bool {
+        self.prefix.iter().all(Option::is_none)
+    }
+}
+
+impl fmt::Display for OmittedGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // trailing wildcards are left out, being under `.items` already covers every element
+        let len = self
+            .prefix
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |idx| idx + 1);
+        if len == 0 {
+            return write!(f, "(root)");
+        }
+        for key in &self.prefix[..len] {
+            match key {
+                Some(key) => write!(f, "{}", key)?,
+                None => write!(f, "[*]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
 fn diff_with<'a>(
     lhs: &'a Value,
     rhs: &'a Value,
-    config: Config,
-    path: Path<'a>,
-    acc: &mut Vec<Difference<'a>>,
+    config: &'a Config,
+    path: &mut PathStack<'a>,
+    acc: &mut Differences,
 ) {
+    if acc.is_done() || config.is_ignored(path.keys()) {
+        return;
+    }
+
     let mut folder = DiffFolder {
         rhs,
         path,
@@ -23,27 +181,28 @@ fn diff_with<'a>(
         config,
     };
 
-    fold_json(lhs, &mut folder);
+    if let Some((pattern, matcher)) = config.path_matcher(folder.path.keys()) {
+        folder.on_matcher(lhs, pattern, matcher);
+    } else if let Some(placeholder) = config.placeholder(rhs) {
+        folder.on_placeholder(lhs, placeholder);
+    } else {
+        fold_json(lhs, &mut folder);
+    }
 }
 
 #[derive(Debug)]
 struct DiffFolder<'a, 'b> {
     rhs: &'a Value,
-    path: Path<'a>,
-    acc: &'b mut Vec<Difference<'a>>,
-    config: Config,
+    path: &'b mut PathStack<'a>,
+    acc: &'b mut Differences,
+    config: &'a Config,
 }
 
 macro_rules! direct_compare {
     ($name:ident) => {
         fn $name(&mut self, lhs: &'a Value) {
             if self.rhs != lhs {
-                self.acc.push(Difference {
-                    lhs: Some(lhs),
-                    rhs: Some(&self.rhs),
-                    path: self.path.clone(),
-                    config: self.config.clone(),
-                });
+                self.push_not_equal(lhs);
             }
         }
     };
@@ -54,18 +213,53 @@ impl<'a, 'b> DiffFolder<'a, 'b> {
     direct_compare!(on_bool);
     direct_compare!(on_string);
 
+    fn on_placeholder(&mut self, lhs: &'a Value, placeholder: Placeholder<'_>) {
+        let custom = match placeholder.arg {
+            None => self.config.placeholder_matcher_named(placeholder.name),
+            Some(_) => None,
+        };
+        match custom {
+            Some(matcher) => self.on_matcher(lhs, &placeholder.source, matcher),
+            None => {
+                if let Err(reason) = placeholder.check(lhs) {
+                    self.push_rejected(lhs, &placeholder.source, reason);
+                }
+            }
+        }
+    }
+
+    /// `name` is only formatted if the matcher rejects the value, so matching doesn't allocate.
+    fn on_matcher(&mut self, lhs: &'a Value, name: &dyn fmt::Display, matcher: &dyn Matcher) {
+        if let Err(reason) = matcher.matches(lhs) {
+            self.push_rejected(lhs, name, reason);
+        }
+    }
+
+    fn push_rejected(&mut self, lhs: &'a Value, matcher: &dyn fmt::Display, reason: String) {
+        let kind = DifferenceKind::MatcherRejected {
+            matcher: matcher.to_string(),
+            reason,
+        };
+        self.push(Some(lhs), Some(self.rhs), kind);
+    }
+
     fn on_number(&mut self, lhs: &'a Value) {
         let is_equal = match self.config.numeric_mode {
             NumericMode::Strict => self.rhs == lhs,
             NumericMode::AssumeFloat => self.rhs.as_f64() == lhs.as_f64(),
+            tolerance => match (lhs.as_f64(), self.rhs.as_f64()) {
+                (Some(lhs_number), Some(rhs_number)) => {
+                    if let Some(delta) = exceeded_tolerance(tolerance, lhs_number, rhs_number) {
+                        let kind = DifferenceKind::NumberOutsideTolerance { delta, tolerance };
+                        self.push(Some(lhs), Some(self.rhs), kind);
+                    }
+                    true
+                }
+                _ => false,
+            },
         };
         if !is_equal {
-            self.acc.push(Difference {
-                lhs: Some(lhs),
-                rhs: Some(self.rhs),
-                path: self.path.clone(),
-                config: self.config.clone(),
-            });
+            self.push_not_equal(lhs);
         }
     }
 
@@ -73,66 +267,135 @@ impl<'a, 'b> DiffFolder<'a, 'b> {
         if let Some(rhs) = self.rhs.as_array() {
             let lhs = lhs.as_array().unwrap();
 
-            match self.config.compare_mode {
-                CompareMode::Inclusive => {
-                    for (idx, rhs) in rhs.iter().enumerate() {
-                        let path = self.path.append(Key::Idx(idx));
+            match self.config.array_sorting_mode {
+                ArraySortingMode::Strict => self.compare_arrays_by_index(lhs, rhs),
+                ArraySortingMode::Ignore => self.compare_arrays_ignoring_order(lhs, rhs),
+                ArraySortingMode::Subsequence => match self.config.compare_mode {
+                    CompareMode::Inclusive => self.compare_arrays_as_subsequence(lhs, rhs),
+                    CompareMode::Strict => self.compare_arrays_by_index(lhs, rhs),
+                },
+            }
+        } else {
+            self.push_not_equal(lhs);
+        }
+    }
 
-                        if let Some(lhs) = lhs.get(idx) {
-                            diff_with(lhs, rhs, self.config.clone(), path, self.acc)
-                        } else {
-                            self.acc.push(Difference {
-                                lhs: None,
-                                rhs: Some(self.rhs),
-                                path,
-                                config: self.config.clone(),
-                            });
-                        }
+    fn compare_arrays_by_index(&mut self, lhs: &'a [Value], rhs: &'a [Value]) {
+        match self.config.compare_mode {
+            CompareMode::Inclusive => {
+                for (idx, rhs) in rhs.iter().enumerate() {
+                    if self.acc.is_done() {
+                        break;
+                    }
+                    let key = KeyRef::Idx(idx);
+
+                    if let Some(lhs) = lhs.get(idx) {
+                        self.descend(key, lhs, rhs)
+                    } else {
+                        self.push_at(key, None, Some(rhs), DifferenceKind::MissingFromLhs);
                     }
                 }
-                CompareMode::Strict => {
-                    let all_keys = rhs
-                        .indexes()
-                        .into_iter()
-                        .chain(lhs.indexes())
-                        .collect::<HashSet<_>>();
-                    for key in all_keys {
-                        let path = self.path.append(Key::Idx(key));
-
-                        match (lhs.get(key), rhs.get(key)) {
-                            (Some(lhs), Some(rhs)) => {
-                                diff_with(lhs, rhs, self.config.clone(), path, self.acc);
-                            }
-                            (None, Some(rhs)) => {
-                                self.acc.push(Difference {
-                                    lhs: None,
-                                    rhs: Some(rhs),
-                                    path,
-                                    config: self.config.clone(),
-                                });
-                            }
-                            (Some(lhs), None) => {
-                                self.acc.push(Difference {
-                                    lhs: Some(lhs),
-                                    rhs: None,
-                                    path,
-                                    config: self.config.clone(),
-                                });
-                            }
-                            (None, None) => {
-                                unreachable!("at least one of the maps should have the key")
-                            }
+            }
+            CompareMode::Strict => {
+                let all_keys = if lhs.len() >= rhs.len() {
+                    lhs.indexes()
+                } else {
+                    rhs.indexes()
+                };
+                for idx in all_keys {
+                    if self.acc.is_done() {
+                        break;
+                    }
+                    let key = KeyRef::Idx(idx);
+
+                    match (lhs.get(idx), rhs.get(idx)) {
+                        (Some(lhs), Some(rhs)) => {
+                            self.descend(key, lhs, rhs);
+                        }
+                        (None, Some(rhs)) => {
+                            self.push_at(key, None, Some(rhs), DifferenceKind::MissingFromLhs);
+                        }
+                        (Some(lhs), None) => {
+                            self.push_at(key, Some(lhs), None, DifferenceKind::MissingFromRhs);
+                        }
+                        (None, None) => {
+                            unreachable!("at least one of the maps should have the key")
                         }
                     }
                 }
             }
-        } else {
-            self.acc.push(Difference {
-                lhs: Some(lhs),
-                rhs: Some(self.rhs),
-                path: self.path.clone(),
-                config: self.config.clone(),
-            });
+        }
+    }
+
+    fn compare_arrays_ignoring_order(&mut self, lhs: &'a [Value], rhs: &'a [Value]) {
+        let costs = self.element_costs(lhs, rhs);
+        let pairs = pair_elements(&costs, lhs.len());
+        self.diff_pairs(lhs, rhs, &costs, &pairs);
+
+        if self.config.compare_mode == CompareMode::Strict {
+            let mut paired_lhs = vec![false; lhs.len()];
+            for lhs_idx in pairs.iter().flatten() {
+                paired_lhs[*lhs_idx] = true;
+            }
+            for (idx, lhs) in lhs.iter().enumerate() {
+                if self.acc.is_done() {
+                    break;
+                }
+                if !paired_lhs[idx] {
+                    let key = KeyRef::Idx(idx);
+                    self.push_at(key, Some(lhs), None, DifferenceKind::MissingFromRhs);
+                }
+            }
+        }
+    }
+
+    fn compare_arrays_as_subsequence(&mut self, lhs: &'a [Value], rhs: &'a [Value]) {
+        let costs = self.element_costs(lhs, rhs);
+        let pairs = pair_subsequence(&costs, lhs.len());
+        self.diff_pairs(lhs, rhs, &costs, &pairs);
+    }
+
+    /// The number of differences between every element of `rhs` and every element of `lhs`.
+    fn element_costs(&mut self, lhs: &'a [Value], rhs: &'a [Value]) -> Vec<Vec<usize>> {
+        let mut costs = Vec::with_capacity(rhs.len());
+        for rhs in rhs {
+            let mut row = Vec::with_capacity(lhs.len());
+            for (idx, lhs) in lhs.iter().enumerate() {
+                let mut acc = self.acc.nested();
+                self.path.push(KeyRef::Idx(idx));
+                diff_with(lhs, rhs, self.config, self.path, &mut acc);
+                self.path.pop();
+                row.push(acc.len());
+            }
+            costs.push(row);
+        }
+        costs
+    }
+
+    fn diff_pairs(
+        &mut self,
+        lhs: &'a [Value],
+        rhs: &'a [Value],
+        costs: &[Vec<usize>],
+        pairs: &[Option<usize>],
+    ) {
+        for (rhs_idx, lhs_idx) in pairs.iter().enumerate() {
+            if self.acc.is_done() {
+                break;
+            }
+            let rhs = &rhs[rhs_idx];
+            match lhs_idx {
+                Some(lhs_idx) => {
+                    if costs[rhs_idx][*lhs_idx] > 0 {
+                        let key = KeyRef::Idx(*lhs_idx);
+                        self.descend(key, &lhs[*lhs_idx], rhs);
+                    }
+                }
+                None => {
+                    let key = KeyRef::Idx(rhs_idx);
+                    self.push_at(key, None, Some(rhs), DifferenceKind::MissingFromLhs);
+                }
+            }
         }
     }
 
@@ -142,45 +405,38 @@ impl<'a, 'b> DiffFolder<'a, 'b> {
 
             match self.config.compare_mode {
                 CompareMode::Inclusive => {
-                    for (key, rhs) in rhs.iter() {
-                        let path = self.path.append(Key::Field(key));
+                    for (field, rhs) in rhs.iter() {
+                        if self.acc.is_done() {
+                            break;
+                        }
+                        let key = KeyRef::Field(field);
 
-                        if let Some(lhs) = lhs.get(key) {
-                            diff_with(lhs, rhs, self.config.clone(), path, self.acc)
+                        if let Some(lhs) = lhs.get(field) {
+                            self.descend(key, lhs, rhs)
                         } else {
-                            self.acc.push(Difference {
-                                lhs: None,
-                                rhs: Some(self.rhs),
-                                path,
-                                config: self.config.clone(),
-                            });
+                            self.push_at(key, None, Some(rhs), DifferenceKind::MissingFromLhs);
                         }
                     }
                 }
                 CompareMode::Strict => {
-                    let all_keys = rhs.keys().chain(lhs.keys()).collect::<HashSet<_>>();
-                    for key in all_keys {
-                        let path = self.path.append(Key::Field(key));
+                    let all_keys = lhs
+                        .keys()
+                        .chain(rhs.keys().filter(|key| !lhs.contains_key(*key)));
+                    for field in all_keys {
+                        if self.acc.is_done() {
+                            break;
+                        }
+                        let key = KeyRef::Field(field);
 
-                        match (lhs.get(key), rhs.get(key)) {
+                        match (lhs.get(field), rhs.get(field)) {
                             (Some(lhs), Some(rhs)) => {
-                                diff_with(lhs, rhs, self.config.clone(), path, self.acc);
+                                self.descend(key, lhs, rhs);
                             }
                             (None, Some(rhs)) => {
-                                self.acc.push(Difference {
-                                    lhs: None,
-                                    rhs: Some(rhs),
-                                    path,
-                                    config: self.config.clone(),
-                                });
+                                self.push_at(key, None, Some(rhs), DifferenceKind::MissingFromLhs);
                             }
                             (Some(lhs), None) => {
-                                self.acc.push(Difference {
-                                    lhs: Some(lhs),
-                                    rhs: None,
-                                    path,
-                                    config: self.config.clone(),
-                                });
+                                self.push_at(key, Some(lhs), None, DifferenceKind::MissingFromRhs);
                             }
                             (None, None) => {
                                 unreachable!("at least one of the maps should have the key")
@@ -190,42 +446,161 @@ impl<'a, 'b> DiffFolder<'a, 'b> {
                 }
             }
         } else {
-            self.acc.push(Difference {
-                lhs: Some(lhs),
-                rhs: Some(self.rhs),
-                path: self.path.clone(),
-                config: self.config.clone(),
-            });
+            self.push_not_equal(lhs);
         }
     }
+
+    fn push_not_equal(&mut self, lhs: &'a Value) {
+        let kind = DifferenceKind::between(lhs, self.rhs);
+        self.push(Some(lhs), Some(self.rhs), kind);
+    }
+
+    /// Compare `lhs` and `rhs`, which are found at `key` under the current path.
+    fn descend(&mut self, key: KeyRef<'a>, lhs: &'a Value, rhs: &'a Value) {
+        self.path.push(key);
+        diff_with(lhs, rhs, self.config, self.path, self.acc);
+        self.path.pop();
+    }
+
+    /// Record a difference at `key` under the current path.
+    fn push_at(
+        &mut self,
+        key: KeyRef<'a>,
+        lhs: Option<&Value>,
+        rhs: Option<&Value>,
+        kind: DifferenceKind,
+    ) {
+        self.path.push(key);
+        self.push(lhs, rhs, kind);
+        self.path.pop();
+    }
+
+    /// Record a difference at the current path.
+    fn push(&mut self, lhs: Option<&Value>, rhs: Option<&Value>, kind: DifferenceKind) {
+        if self.config.is_ignored(self.path.keys()) {
+            return;
+        }
+        if self.acc.is_full() {
+            self.acc.omit(self.path);
+            return;
+        }
+
+        self.acc.found.push(Difference {
+            path: self.path.to_path(),
+            lhs: lhs.cloned(),
+            rhs: rhs.cloned(),
+            kind,
+            compare_mode: self.config.compare_mode,
+            line: None,
+        });
+    }
+}
+
+/// A single difference between two JSON values.
+///
+/// Returned by [`diff_json`](crate::diff_json). The `Display` implementation produces the same
+/// message that [`assert_json_matches`](crate::assert_json_matches) panics with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    path: Path,
+    lhs: Option<Value>,
+    rhs: Option<Value>,
+    kind: DifferenceKind,
+    compare_mode: CompareMode,
+    line: Option<usize>,
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct Difference<'a> {
-    path: Path<'a>,
-    lhs: Option<&'a Value>,
-    rhs: Option<&'a Value>,
-    config: Config,
+impl Difference {
+    /// The path to where the difference was found.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The value on the left hand side, if any.
+    ///
+    /// When using [`CompareMode::Inclusive`] this is the "actual" value. It is `None` if the value
+    /// is missing from the left hand side, in which case [`Difference::rhs`] is the value that was
+    /// expected there.
+    pub fn lhs(&self) -> Option<&Value> {
+        self.lhs.as_ref()
+    }
+
+    /// The value on the right hand side, if any.
+    ///
+    /// When using [`CompareMode::Inclusive`] this is the "expected" value. It is `None` if the
+    /// value is missing from the right hand side, in which case [`Difference::lhs`] is the value
+    /// that wasn't expected there.
+    pub fn rhs(&self) -> Option<&Value> {
+        self.rhs.as_ref()
+    }
+
+    /// What kind of difference this is.
+    pub fn kind(&self) -> &DifferenceKind {
+        &self.kind
+    }
+
+    /// The compare mode that was used to find the difference.
+    pub fn compare_mode(&self) -> CompareMode {
+        self.compare_mode
+    }
+
+    /// The line in the left hand side JSON text where the difference is, starting from 1.
+    ///
+    /// Only set when comparing JSON text with
+    /// [`assert_json_text_matches_no_panic`](crate::assert_json_text_matches_no_panic). For
+    /// values missing from the left hand side it is the line of the closest ancestor that isn't
+    /// missing.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub(crate) fn set_line(&mut self, line: usize) {
+        self.line = Some(line);
+    }
+
+    /// The path, along with the line if known, for the first line of messages.
+    fn location(&self) -> Location<'_> {
+        Location(self)
+    }
 }
 
-impl<'a> fmt::Display for Difference<'a> {
+impl fmt::Display for Difference {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let json_to_string = |json: &Value| serde_json::to_string_pretty(json).unwrap();
 
-        match (&self.config.compare_mode, &self.lhs, &self.rhs) {
+        if let (DifferenceKind::MatcherRejected { matcher, reason }, Some(lhs)) =
+            (&self.kind, &self.lhs)
+        {
+            writeln!(
+                f,
+                "json atom at path {} was rejected by matcher \"{}\": {}",
+                self.location(),
+                matcher,
+                reason
+            )?;
+            match self.compare_mode {
+                CompareMode::Inclusive => writeln!(f, "    actual:")?,
+                CompareMode::Strict => writeln!(f, "    lhs:")?,
+            }
+            return write!(f, "{}", json_to_string(lhs).indent(8));
+        }
+
+        match (&self.compare_mode, &self.lhs, &self.rhs) {
             (CompareMode::Inclusive, Some(actual), Some(expected)) => {
-                writeln!(f, "json atoms at path \"{}\" are not equal:", self.path)?;
+                self.write_not_equal(f)?;
                 writeln!(f, "    expected:")?;
                 writeln!(f, "{}", json_to_string(expected).indent(8))?;
                 writeln!(f, "    actual:")?;
                 write!(f, "{}", json_to_string(actual).indent(8))?;
             }
-            (CompareMode::Inclusive, None, Some(_expected)) => {
-                write!(
+            (CompareMode::Inclusive, None, Some(expected)) => {
+                writeln!(
                     f,
-                    "json atom at path \"{}\" is missing from actual",
-                    self.path
+                    "json atom at path {} is missing from actual:",
+                    self.location()
                 )?;
+                writeln!(f, "    expected:")?;
+                write!(f, "{}", json_to_string(expected).indent(8))?;
             }
             (CompareMode::Inclusive, Some(_actual), None) => {
                 unreachable!("stuff missing actual wont produce an error")
@@ -233,17 +608,29 @@ impl<'a> fmt::Display for Difference<'a> {
             (CompareMode::Inclusive, None, None) => unreachable!("can't both be missing"),
 
             (CompareMode::Strict, Some(lhs), Some(rhs)) => {
-                writeln!(f, "json atoms at path \"{}\" are not equal:", self.path)?;
+                self.write_not_equal(f)?;
                 writeln!(f, "    lhs:")?;
                 writeln!(f, "{}", json_to_string(lhs).indent(8))?;
                 writeln!(f, "    rhs:")?;
                 write!(f, "{}", json_to_string(rhs).indent(8))?;
             }
-            (CompareMode::Strict, None, Some(_)) => {
-                write!(f, "json atom at path \"{}\" is missing from lhs", self.path)?;
+            (CompareMode::Strict, None, Some(rhs)) => {
+                writeln!(
+                    f,
+                    "json atom at path {} is missing from lhs:",
+                    self.location()
+                )?;
+                writeln!(f, "    rhs:")?;
+                write!(f, "{}", json_to_string(rhs).indent(8))?;
             }
-            (CompareMode::Strict, Some(_), None) => {
-                write!(f, "json atom at path \"{}\" is missing from rhs", self.path)?;
+            (CompareMode::Strict, Some(lhs), None) => {
+                writeln!(
+                    f,
+                    "json atom at path {} is missing from rhs:",
+                    self.location()
+                )?;
+                writeln!(f, "    lhs:")?;
+                write!(f, "{}", json_to_string(lhs).indent(8))?;
             }
             (CompareMode::Strict, None, None) => unreachable!("can't both be missing"),
         }
@@ -252,46 +639,187 @@ impl<'a> fmt::Display for Difference<'a> {
     }
 }
 
+impl Difference {
+    pub(crate) fn write_not_equal<W: fmt::Write>(&self, f: &mut W) -> fmt::Result {
+        match &self.kind {
+            DifferenceKind::NumberOutsideTolerance { delta, tolerance } => match tolerance {
+                NumericMode::AbsoluteTolerance(allowed) => writeln!(
+                    f,
+                    "json numbers at path {} differ by {}, which is more than the absolute tolerance of {}:",
+                    self.location(), delta, allowed
+                ),
+                NumericMode::RelativeTolerance(allowed) => writeln!(
+                    f,
+                    "json numbers at path {} differ by a relative {}, which is more than the relative tolerance of {}:",
+                    self.location(), delta, allowed
+                ),
+                NumericMode::Ulps(allowed) => writeln!(
+                    f,
+                    "json numbers at path {} differ by {} ULPs, which is more than the tolerance of {} ULPs:",
+                    self.location(), delta, allowed
+                ),
+                NumericMode::Strict | NumericMode::AssumeFloat => {
+                    unreachable!("not a tolerance")
+                }
+            },
+            _ => writeln!(f, "json atoms at path {} are not equal:", self.location()),
+        }
+    }
+}
+
+struct Location<'a>(&'a Difference);
+
+impl fmt::Display for Location<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"{}\"", self.0.path)?;
+        if let Some(line) = self.0.line {
+            let side = match self.0.compare_mode {
+                CompareMode::Inclusive => "actual",
+                CompareMode::Strict => "lhs",
+            };
+            write!(f, " (line {} of {})", line, side)?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of a [`Difference`].
+///
+/// More kinds may be added in the future, so matches need a wildcard arm.
 #[derive(Debug, Clone, PartialEq)]
-enum Path<'a> {
-    Root,
-    Keys(Vec<Key<'a>>),
+#[non_exhaustive]
+pub enum DifferenceKind {
+    /// Both sides have a value of the same JSON type but the values aren't equal.
+    ValueMismatch,
+    /// Both sides are numbers but they differ by more than the tolerance allows.
+    ///
+    /// Only produced when using one of the tolerance [`NumericMode`]s.
+    NumberOutsideTolerance {
+        /// How much the numbers differ by, in the same unit as the tolerance.
+        delta: f64,
+        /// The numeric mode with the tolerance that was exceeded.
+        tolerance: NumericMode,
+    },
+    /// The two sides have values of different JSON types, for example a string and an object.
+    TypeMismatch,
+    /// The value on the left hand side was rejected by a placeholder or a [`Matcher`].
+    MatcherRejected {
+        /// The matcher that rejected the value. This is the placeholder, such as `${any_uuid}`,
+        /// or the path pattern the matcher was registered for, such as `.users[*].country`.
+        matcher: String,
+        /// Why the matcher rejected the value.
+        reason: String,
+    },
+    /// The value is present on the right hand side but missing from the left hand side.
+    ///
+    /// When using [`CompareMode::Inclusive`] this means the value is missing from "actual".
+    MissingFromLhs,
+    /// The value is present on the left hand side but missing from the right hand side.
+    MissingFromRhs,
 }
 
-impl<'a> Path<'a> {
-    fn append(&self, next: Key<'a>) -> Path<'a> {
-        match self {
-            Path::Root => Path::Keys(vec![next]),
-            Path::Keys(list) => {
-                let mut copy = list.clone();
-                copy.push(next);
-                Path::Keys(copy)
-            }
+impl DifferenceKind {
+    fn between(lhs: &Value, rhs: &Value) -> Self {
+        if std::mem::discriminant(lhs) == std::mem::discriminant(rhs) {
+            DifferenceKind::ValueMismatch
+        } else {
+            DifferenceKind::TypeMismatch
         }
     }
 }
 
-impl<'a> fmt::Display for Path<'a> {
+/// A path to a value inside a JSON document, such as `.data.users[0].name`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path {
+    keys: Vec<Key>,
+}
+
+impl Path {
+    /// The keys that make up the path, starting from the root.
+    ///
+    /// The root path has no keys.
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Is this the path to the root value?
+    pub fn is_root(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub(crate) fn append(&self, next: Key) -> Path {
+        let mut keys = self.keys.clone();
+        keys.push(next);
+        Path { keys }
+    }
+}
+
+impl fmt::Display for Path {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Path::Root => write!(f, "(root)"),
-            Path::Keys(keys) => {
-                for key in keys {
-                    write!(f, "{}", key)?;
-                }
-                Ok(())
-            }
+        if self.is_root() {
+            return write!(f, "(root)");
         }
+        for key in &self.keys {
+            write!(f, "{}", key)?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-enum Key<'a> {
+/// The path to the value currently being compared.
+///
+/// Keys are pushed and popped as the values are traversed and borrow from the values, so
+/// descending doesn't allocate. An owned [`Path`] is only created when a difference is recorded.
+#[derive(Debug, Default)]
+pub(crate) struct PathStack<'a> {
+    keys: Vec<KeyRef<'a>>,
+}
+
+impl<'a> PathStack<'a> {
+    pub(crate) fn keys(&self) -> &[KeyRef<'a>] {
+        &self.keys
+    }
+
+    pub(crate) fn push(&mut self, key: KeyRef<'a>) {
+        self.keys.push(key);
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.keys.pop();
+    }
+
+    fn to_path(&self) -> Path {
+        let keys = self.keys.iter().map(|key| key.into_key()).collect();
+        Path { keys }
+    }
+}
+
+/// A borrowed [`Key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeyRef<'a> {
     Idx(usize),
     Field(&'a str),
 }
 
-impl<'a> fmt::Display for Key<'a> {
+impl KeyRef<'_> {
+    fn into_key(self) -> Key {
+        match self {
+            KeyRef::Idx(idx) => Key::Idx(idx),
+            KeyRef::Field(field) => Key::Field(field.to_string()),
+        }
+    }
+}
+
+/// A single segment of a [`Path`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// An index into an array.
+    Idx(usize),
+    /// A field of an object.
+    Field(String),
+}
+
+impl fmt::Display for Key {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Key::Idx(idx) => write!(f, "[{}]", idx),
@@ -300,6 +828,156 @@ impl<'a> fmt::Display for Key<'a> {
     }
 }
 
+/// Returns how much two numbers differ by if that is more than `tolerance` allows.
+///
+/// The difference is measured in the same unit as the tolerance.
+fn exceeded_tolerance(tolerance: NumericMode, lhs: f64, rhs: f64) -> Option<f64> {
+    let (delta, allowed) = match tolerance {
+        NumericMode::AbsoluteTolerance(allowed) => ((lhs - rhs).abs(), allowed),
+        NumericMode::RelativeTolerance(allowed) => {
+            let largest = lhs.abs().max(rhs.abs());
+            let delta = if largest == 0.0 {
+                0.0
+            } else {
+                (lhs - rhs).abs() / largest
+            };
+            (delta, allowed)
+        }
+        NumericMode::Ulps(allowed) => (ulps_between(lhs, rhs) as f64, allowed as f64),
+        NumericMode::Strict | NumericMode::AssumeFloat => {
+            unreachable!("not a tolerance")
+        }
+    };
+
+    // written this way round so a NaN is never within the tolerance
+    if delta <= allowed {
+        None
+    } else {
+        Some(delta)
+    }
+}
+
+/// The number of representable floats between `lhs` and `rhs`.
+fn ulps_between(lhs: f64, rhs: f64) -> u64 {
+    // Map the bits onto integers that are ordered the same way as the floats, so positive and
+    // negative zero are both 0 and the distance across zero is the sum of the distances to it.
+    fn ordered(number: f64) -> i128 {
+        let bits = number.to_bits();
+        let magnitude = i128::from(bits & !(1 << 63));
+        if bits >> 63 == 1 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    let distance = (ordered(lhs) - ordered(rhs)).unsigned_abs();
+    u64::try_from(distance).unwrap_or(u64::MAX)
+}
+
+/// Pair up the elements of two arrays without regard to their order.
+///
+/// `costs[rhs][lhs]` is the number of differences between the two elements. Elements without any
+/// differences are paired first, picking a maximum matching so that an element which matches
+/// several others can't steal the only partner of another element. Whatever is left is paired
+/// with the remaining element that has the fewest differences.
+///
+/// Returns the index of the `lhs` element paired with each `rhs` element, if any.
+fn pair_elements(costs: &[Vec<usize>], lhs_len: usize) -> Vec<Option<usize>> {
+    fn find_partner(
+        rhs: usize,
+        costs: &[Vec<usize>],
+        seen: &mut [bool],
+        partner_of_lhs: &mut [Option<usize>],
+    ) -> bool {
+        for lhs in 0..seen.len() {
+            if costs[rhs][lhs] != 0 || seen[lhs] {
+                continue;
+            }
+            seen[lhs] = true;
+
+            let available = match partner_of_lhs[lhs] {
+                None => true,
+                Some(other) => find_partner(other, costs, seen, partner_of_lhs),
+            };
+            if available {
+                partner_of_lhs[lhs] = Some(rhs);
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut partner_of_lhs = vec![None; lhs_len];
+    for rhs in 0..costs.len() {
+        let mut seen = vec![false; lhs_len];
+        find_partner(rhs, costs, &mut seen, &mut partner_of_lhs);
+    }
+
+    let mut pairs = vec![None; costs.len()];
+    for (lhs, rhs) in partner_of_lhs.iter().enumerate() {
+        if let Some(rhs) = rhs {
+            pairs[*rhs] = Some(lhs);
+        }
+    }
+
+    for rhs in 0..costs.len() {
+        if pairs[rhs].is_some() {
+            continue;
+        }
+        let closest = (0..lhs_len)
+            .filter(|lhs| partner_of_lhs[*lhs].is_none())
+            .min_by_key(|lhs| costs[rhs][*lhs]);
+        if let Some(lhs) = closest {
+            partner_of_lhs[lhs] = Some(rhs);
+            pairs[rhs] = Some(lhs);
+        }
+    }
+
+    pairs
+}
+
+/// Pair up the elements of two arrays such that the `rhs` elements appear in the same relative
+/// order in `lhs`, but not necessarily next to each other.
+///
+/// Each `rhs` element is paired with the first element without differences that comes after the
+/// previous pair. Elements that can't be paired that way are paired with the closest unused element
+/// that keeps the order intact, if there is one.
+///
+/// Returns the index of the `lhs` element paired with each `rhs` element, if any.
+fn pair_subsequence(costs: &[Vec<usize>], lhs_len: usize) -> Vec<Option<usize>> {
+    let mut pairs = vec![None; costs.len()];
+
+    let mut next = 0;
+    for (rhs, pair) in pairs.iter_mut().enumerate() {
+        if let Some(lhs) = (next..lhs_len).find(|lhs| costs[rhs][*lhs] == 0) {
+            *pair = Some(lhs);
+            next = lhs + 1;
+        }
+    }
+
+    let mut lower = 0;
+    for rhs in 0..costs.len() {
+        if let Some(lhs) = pairs[rhs] {
+            lower = lhs + 1;
+            continue;
+        }
+        let upper = pairs[rhs + 1..]
+            .iter()
+            .flatten()
+            .next()
+            .copied()
+            .unwrap_or(lhs_len);
+        let closest = (lower..upper).min_by_key(|lhs| costs[rhs][*lhs]);
+        if let Some(lhs) = closest {
+            pairs[rhs] = Some(lhs);
+            lower = lhs + 1;
+        }
+    }
+
+    pairs
+}
+
 fn fold_json<'a>(json: &'a Value, folder: &mut DiffFolder<'a, '_>) {
     match json {
         Value::Null => folder.on_null(json),
@@ -322,66 +1000,66 @@ mod test {
         let diffs = diff(
             &json!(null),
             &json!(null),
-            Config::new(CompareMode::Inclusive),
+            &Config::new(CompareMode::Inclusive),
         );
         assert_eq!(diffs, vec![]);
 
         let diffs = diff(
             &json!(false),
             &json!(false),
-            Config::new(CompareMode::Inclusive),
+            &Config::new(CompareMode::Inclusive),
         );
         assert_eq!(diffs, vec![]);
 
         let diffs = diff(
             &json!(true),
             &json!(true),
-            Config::new(CompareMode::Inclusive),
+            &Config::new(CompareMode::Inclusive),
         );
         assert_eq!(diffs, vec![]);
 
         let diffs = diff(
             &json!(false),
             &json!(true),
-            Config::new(CompareMode::Inclusive),
+            &Config::new(CompareMode::Inclusive),
         );
         assert_eq!(diffs.len(), 1);
 
         let diffs = diff(
             &json!(true),
             &json!(false),
-            Config::new(CompareMode::Inclusive),
+            &Config::new(CompareMode::Inclusive),
         );
         assert_eq!(diffs.len(), 1);
 
         let actual = json!(1);
         let expected = json!(1);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
 
         let actual = json!(2);
         let expected = json!(1);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         let actual = json!(1);
         let expected = json!(2);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         let actual = json!(1.0);
         let expected = json!(1.0);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
 
         let actual = json!(1);
         let expected = json!(1.0);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         let actual = json!(1.0);
         let expected = json!(1);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         let actual = json!(1);
@@ -389,7 +1067,7 @@ mod test {
         let diffs = diff(
             &actual,
             &expected,
-            Config::new(CompareMode::Inclusive).numeric_mode(NumericMode::AssumeFloat),
+            &Config::new(CompareMode::Inclusive).numeric_mode(NumericMode::AssumeFloat),
         );
         assert_eq!(diffs, vec![]);
 
@@ -398,7 +1076,7 @@ mod test {
         let diffs = diff(
             &actual,
             &expected,
-            Config::new(CompareMode::Inclusive).numeric_mode(NumericMode::AssumeFloat),
+            &Config::new(CompareMode::Inclusive).numeric_mode(NumericMode::AssumeFloat),
         );
         assert_eq!(diffs, vec![]);
     }
@@ -408,52 +1086,52 @@ mod test {
         // empty
         let actual = json!([]);
         let expected = json!([]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
 
         let actual = json!([1]);
         let expected = json!([]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 0);
 
         let actual = json!([]);
         let expected = json!([1]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         // eq
         let actual = json!([1]);
         let expected = json!([1]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
 
         // actual longer
         let actual = json!([1, 2]);
         let expected = json!([1]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
 
         // expected longer
         let actual = json!([1]);
         let expected = json!([1, 2]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         // eq length but different
         let actual = json!([1, 3]);
         let expected = json!([1, 2]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         // different types
         let actual = json!(1);
         let expected = json!([1]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         let actual = json!([1]);
         let expected = json!(1);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
     }
 
@@ -461,22 +1139,22 @@ mod test {
     fn test_array_strict() {
         let actual = json!([]);
         let expected = json!([]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Strict));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Strict));
         assert_eq!(diffs.len(), 0);
 
         let actual = json!([1, 2]);
         let expected = json!([1, 2]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Strict));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Strict));
         assert_eq!(diffs.len(), 0);
 
         let actual = json!([1]);
         let expected = json!([1, 2]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Strict));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Strict));
         assert_eq!(diffs.len(), 1);
 
         let actual = json!([1, 2]);
         let expected = json!([1]);
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Strict));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Strict));
         assert_eq!(diffs.len(), 1);
     }
 
@@ -484,32 +1162,32 @@ mod test {
     fn test_object() {
         let actual = json!({});
         let expected = json!({});
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
 
         let actual = json!({ "a": 1 });
         let expected = json!({ "a": 1 });
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
 
         let actual = json!({ "a": 1, "b": 123 });
         let expected = json!({ "a": 1 });
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
 
         let actual = json!({ "a": 1 });
         let expected = json!({ "b": 1 });
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         let actual = json!({ "a": 1 });
         let expected = json!({ "a": 2 });
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs.len(), 1);
 
         let actual = json!({ "a": { "b": true } });
         let expected = json!({ "a": {} });
-        let diffs = diff(&actual, &expected, Config::new(CompareMode::Inclusive));
+        let diffs = diff(&actual, &expected, &Config::new(CompareMode::Inclusive));
         assert_eq!(diffs, vec![]);
     }
 
@@ -517,16 +1195,482 @@ mod test {
     fn test_object_strict() {
         let lhs = json!({});
         let rhs = json!({ "a": 1 });
-        let diffs = diff(&lhs, &rhs, Config::new(CompareMode::Strict));
+        let diffs = diff(&lhs, &rhs, &Config::new(CompareMode::Strict));
         assert_eq!(diffs.len(), 1);
 
         let lhs = json!({ "a": 1 });
         let rhs = json!({});
-        let diffs = diff(&lhs, &rhs, Config::new(CompareMode::Strict));
+        let diffs = diff(&lhs, &rhs, &Config::new(CompareMode::Strict));
         assert_eq!(diffs.len(), 1);
 
         let json = json!({ "a": 1 });
-        let diffs = diff(&json, &json, Config::new(CompareMode::Strict));
+        let diffs = diff(&json, &json, &Config::new(CompareMode::Strict));
+        assert_eq!(diffs, vec![]);
+    }
+
+    #[test]
+    fn test_difference_kinds() {
+        let diffs = diff(
+            &json!({ "a": 1 }),
+            &json!({ "a": 2 }),
+            &Config::new(CompareMode::Strict),
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::ValueMismatch);
+
+        let diffs = diff(
+            &json!({ "a": 1 }),
+            &json!({ "a": [1] }),
+            &Config::new(CompareMode::Strict),
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::TypeMismatch);
+
+        let diffs = diff(
+            &json!({}),
+            &json!({ "a": 1 }),
+            &Config::new(CompareMode::Strict),
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::MissingFromLhs);
+        assert_eq!(diffs[0].lhs(), None);
+        assert_eq!(diffs[0].rhs(), Some(&json!(1)));
+
+        let diffs = diff(
+            &json!([1, 2]),
+            &json!([1]),
+            &Config::new(CompareMode::Strict),
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::MissingFromRhs);
+        assert_eq!(diffs[0].lhs(), Some(&json!(2)));
+        assert_eq!(diffs[0].rhs(), None);
+    }
+
+    #[test]
+    fn test_difference_path() {
+        let diffs = diff(
+            &json!({ "a": [1, { "b": true }] }),
+            &json!({ "a": [1, { "b": false }] }),
+            &Config::new(CompareMode::Inclusive),
+        );
+        assert_eq!(diffs.len(), 1);
+
+        let path = diffs[0].path();
+        assert!(!path.is_root());
+        assert_eq!(
+            path.keys(),
+            &[
+                Key::Field("a".to_string()),
+                Key::Idx(1),
+                Key::Field("b".to_string())
+            ]
+        );
+        assert_eq!(path.to_string(), ".a[1].b");
+
+        let diffs = diff(&json!(1), &json!(2), &Config::new(CompareMode::Strict));
+        assert!(diffs[0].path().is_root());
+        assert_eq!(diffs[0].path().to_string(), "(root)");
+    }
+
+    #[test]
+    fn test_array_ignoring_order() {
+        let config = Config::new(CompareMode::Strict).array_sorting_mode(ArraySortingMode::Ignore);
+
+        let diffs = diff(&json!([1, 2, 3]), &json!([3, 1, 2]), &config);
+        assert_eq!(diffs, vec![]);
+
+        let diffs = diff(&json!([1, 1, 2]), &json!([1, 2, 2]), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path().to_string(), "[1]");
+        assert_eq!(diffs[0].lhs(), Some(&json!(1)));
+        assert_eq!(diffs[0].rhs(), Some(&json!(2)));
+
+        let diffs = diff(&json!([1, 2]), &json!([2]), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::MissingFromRhs);
+        assert_eq!(diffs[0].path().to_string(), "[0]");
+
+        let diffs = diff(&json!([2]), &json!([1, 2]), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::MissingFromLhs);
+        assert_eq!(diffs[0].path().to_string(), "[0]");
+
+        let diffs = diff(
+            &json!([{ "id": 2, "name": "b" }, { "id": 1, "name": "a" }]),
+            &json!([{ "id": 1, "name": "a" }, { "id": 2, "name": "c" }]),
+            &config,
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path().to_string(), "[0].name");
+    }
+
+    #[test]
+    fn test_array_ignoring_order_inclusive() {
+        let config =
+            Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Ignore);
+
+        let diffs = diff(&json!([1, 2, 3]), &json!([3, 1]), &config);
+        assert_eq!(diffs, vec![]);
+
+        // `{}` is included in both elements, so it must not take the only partner of `{ "a": 1 }`
+        let diffs = diff(
+            &json!([{ "a": 1 }, { "b": 2 }]),
+            &json!([{}, { "a": 1 }]),
+            &config,
+        );
+        assert_eq!(diffs, vec![]);
+
+        let diffs = diff(
+            &json!([{ "id": 1, "tags": ["x", "y"] }, { "id": 2, "tags": [] }]),
+            &json!([{ "tags": ["y"] }]),
+            &config,
+        );
+        assert_eq!(diffs, vec![]);
+
+        let diffs = diff(&json!([1, 2]), &json!([2, 2]), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path().to_string(), "[0]");
+
+        let diffs = diff(&json!([1]), &json!([1, 2]), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::MissingFromLhs);
+        assert_eq!(diffs[0].path().to_string(), "[1]");
+    }
+
+    #[test]
+    fn test_array_subsequence() {
+        let config =
+            Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Subsequence);
+
+        let diffs = diff(&json!([1, 2, 3, 4]), &json!([2, 4]), &config);
+        assert_eq!(diffs, vec![]);
+
+        let diffs = diff(&json!([1, 2, 3, 4]), &json!([]), &config);
+        assert_eq!(diffs, vec![]);
+
+        let diffs = diff(
+            &json!([{ "id": 1 }, { "id": 5, "name": "a" }]),
+            &json!([{ "id": 5 }]),
+            &config,
+        );
+        assert_eq!(diffs, vec![]);
+
+        // out of order
+        let diffs = diff(&json!([1, 2, 3]), &json!([3, 1]), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path().to_string(), "[1]");
+        assert_eq!(diffs[0].kind(), &DifferenceKind::MissingFromLhs);
+
+        // closest element in between the neighbouring matches
+        let diffs = diff(
+            &json!([{ "id": 1 }, { "id": 2, "name": "b" }, { "id": 3 }]),
+            &json!([{ "id": 1 }, { "id": 2, "name": "c" }, { "id": 3 }]),
+            &config,
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path().to_string(), "[1].name");
+
+        // strict mode compares by index
+        let config =
+            Config::new(CompareMode::Strict).array_sorting_mode(ArraySortingMode::Subsequence);
+        let diffs = diff(&json!([1, 2, 3]), &json!([1, 3]), &config);
+        assert_eq!(diffs.len(), 2);
+    }
+
+    #[test]
+    fn test_numeric_tolerance() {
+        let config =
+            Config::new(CompareMode::Strict).numeric_mode(NumericMode::AbsoluteTolerance(0.1));
+        assert_eq!(diff(&json!(1), &json!(1.05), &config), vec![]);
+        assert_eq!(diff(&json!(-0.05), &json!(0.05), &config), vec![]);
+        let diffs = diff(&json!(1), &json!(1.5), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(
+            diffs[0].kind(),
+            &DifferenceKind::NumberOutsideTolerance {
+                delta: 0.5,
+                tolerance: NumericMode::AbsoluteTolerance(0.1),
+            }
+        );
+
+        let config =
+            Config::new(CompareMode::Strict).numeric_mode(NumericMode::RelativeTolerance(0.01));
+        assert_eq!(diff(&json!(1000), &json!(1009), &config), vec![]);
+        assert_eq!(diff(&json!(0), &json!(0.0), &config), vec![]);
+        assert_eq!(diff(&json!(1), &json!(1.02), &config).len(), 1);
+        assert_eq!(diff(&json!(0), &json!(1e-10), &config).len(), 1);
+
+        let config = Config::new(CompareMode::Strict).numeric_mode(NumericMode::Ulps(2));
+        assert_eq!(diff(&json!(0.1 + 0.2), &json!(0.3), &config), vec![]);
+        assert_eq!(diff(&json!(0.0), &json!(-0.0), &config), vec![]);
+        assert_eq!(diff(&json!(1.0), &json!(1.0001), &config).len(), 1);
+
+        // a tolerance doesn't make other types equal to numbers
+        let diffs = diff(&json!(1), &json!("1"), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::TypeMismatch);
+    }
+
+    #[test]
+    fn test_ulps_between() {
+        assert_eq!(ulps_between(1.0, 1.0), 0);
+        assert_eq!(ulps_between(0.0, -0.0), 0);
+        assert_eq!(ulps_between(1.0, f64::from_bits(1.0f64.to_bits() + 3)), 3);
+        assert_eq!(ulps_between(f64::from_bits(1), -f64::from_bits(1)), 2);
+        assert_eq!(ulps_between(f64::MAX, f64::MIN), 2 * f64::MAX.to_bits());
+    }
+
+    #[test]
+    fn test_ignored_paths() {
+        let lhs = json!({ "id": 1, "data": { "users": [{ "etag": "a", "name": "Bob" }] } });
+        let rhs = json!({ "id": 2, "data": { "users": [{ "etag": "b", "name": "Bob" }] } });
+
+        for compare_mode in [CompareMode::Inclusive, CompareMode::Strict] {
+            let config = Config::new(compare_mode);
+            assert_eq!(diff(&lhs, &rhs, &config).len(), 2);
+
+            let config = Config::new(compare_mode)
+                .ignore_path(".id")
+                .ignore_path(".data.users[*].etag");
+            assert_eq!(diff(&lhs, &rhs, &config), vec![]);
+
+            let config = Config::new(compare_mode)
+                .ignore_path(".id")
+                .ignore_path("..etag");
+            assert_eq!(diff(&lhs, &rhs, &config), vec![]);
+        }
+
+        // missing values are ignored as well
+        let config = Config::new(CompareMode::Strict).ignore_path(".*.created_at");
+        let diffs = diff(
+            &json!({ "user": { "created_at": 1 }, "post": {} }),
+            &json!({ "user": {}, "post": { "created_at": 2 } }),
+            &config,
+        );
+        assert_eq!(diffs, vec![]);
+
+        let config = Config::new(CompareMode::Inclusive).ignore_path("[1]");
+        assert_eq!(diff(&json!([1]), &json!([1, 2]), &config), vec![]);
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let config = Config::new(CompareMode::Inclusive).enable_placeholders();
+
+        let diffs = diff(
+            &json!({ "id": 1, "name": "Bob", "tags": ["a"] }),
+            &json!({ "id": "${any_number}", "name": "${regex:^B}", "tags": "${non_empty}" }),
+            &config,
+        );
+        assert_eq!(diffs, vec![]);
+
+        let diffs = diff(&json!({}), &json!({ "id": "${any}" }), &config);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::MissingFromLhs);
+
+        let diffs = diff(
+            &json!({ "id": "1" }),
+            &json!({ "id": "${any_number}" }),
+            &config,
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(
+            diffs[0].kind(),
+            &DifferenceKind::MatcherRejected {
+                matcher: "${any_number}".to_string(),
+                reason: "expected a number".to_string(),
+            }
+        );
+        assert_eq!(diffs[0].lhs(), Some(&json!("1")));
+
+        // placeholders are literals unless enabled
+        let config = Config::new(CompareMode::Inclusive);
+        let diffs = diff(&json!(1), &json!("${any}"), &config);
+        assert_eq!(diffs.len(), 1);
+
+        // placeholders are only recognized on the right hand side
+        let config = Config::new(CompareMode::Strict).enable_placeholders();
+        let diffs = diff(&json!("${any}"), &json!(1), &config);
+        assert_eq!(diffs.len(), 1);
+        let diffs = diff(&json!([1, 2]), &json!(["${any}", "${any}"]), &config);
         assert_eq!(diffs, vec![]);
     }
+
+    #[test]
+    fn test_custom_matchers() {
+        fn positive(actual: &Value) -> Result<(), String> {
+            match actual.as_f64() {
+                Some(n) if n > 0.0 => Ok(()),
+                _ => Err("expected a positive number".to_string()),
+            }
+        }
+
+        let config = Config::new(CompareMode::Strict).matcher_at(".items[*].price", positive);
+        let diffs = diff(
+            &json!({ "items": [{ "price": 1 }, { "price": -1 }] }),
+            &json!({ "items": [{ "price": null }, { "price": null }] }),
+            &config,
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path().to_string(), ".items[1].price");
+        assert_eq!(
+            diffs[0].kind(),
+            &DifferenceKind::MatcherRejected {
+                matcher: ".items[*].price".to_string(),
+                reason: "expected a positive number".to_string(),
+            }
+        );
+
+        // the path must be present on both sides
+        let diffs = diff(
+            &json!({ "items": [{}] }),
+            &json!({ "items": [{ "price": 1 }] }),
+            &config,
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind(), &DifferenceKind::MissingFromLhs);
+
+        let config = Config::new(CompareMode::Inclusive)
+            .placeholder_matcher("positive", positive)
+            .placeholder_matcher("any_number", |_: &Value| Err("overridden".to_string()));
+        assert_eq!(diff(&json!(2), &json!("${positive}"), &config), vec![]);
+        assert_eq!(diff(&json!(-2), &json!("${positive}"), &config).len(), 1);
+        let diffs = diff(&json!(2), &json!("${any_number}"), &config);
+        assert_eq!(
+            diffs[0].kind(),
+            &DifferenceKind::MatcherRejected {
+                matcher: "${any_number}".to_string(),
+                reason: "overridden".to_string(),
+            }
+        );
+        // built-in placeholders still work
+        assert_eq!(diff(&json!("a"), &json!("${any_string}"), &config), vec![]);
+    }
+
+    #[test]
+    fn test_differences_are_ordered() {
+        let lhs = json!({ "b": 1, "a": [1, 2], "d": 1, "c": { "x": 1, "y": 1 } });
+        let rhs = json!({ "c": { "y": 2, "z": 2 }, "e": 2, "a": [2], "b": 2 });
+
+        let paths = |compare_mode| {
+            diff(&lhs, &rhs, &Config::new(compare_mode))
+                .iter()
+                .map(|d| d.path().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let expected = vec![".a[0]", ".a[1]", ".b", ".c.x", ".c.y", ".c.z", ".d", ".e"];
+        for _ in 0..10 {
+            assert_eq!(paths(CompareMode::Strict), expected);
+        }
+
+        assert_eq!(
+            paths(CompareMode::Inclusive),
+            vec![".a[0]", ".b", ".c.y", ".c.z", ".e"]
+        );
+    }
+
+    #[test]
+    fn test_missing_values() {
+        let lhs = json!({ "a": [1], "b": true });
+        let rhs = json!({ "a": [1, { "x": 2 }], "c": "foo" });
+
+        let values = |compare_mode| {
+            diff(&lhs, &rhs, &Config::new(compare_mode))
+                .into_iter()
+                .map(|d| (d.path().to_string(), d.lhs().cloned(), d.rhs().cloned()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            values(CompareMode::Inclusive),
+            vec![
+                (".a[1]".to_string(), None, Some(json!({ "x": 2 }))),
+                (".c".to_string(), None, Some(json!("foo"))),
+            ]
+        );
+        assert_eq!(
+            values(CompareMode::Strict),
+            vec![
+                (".a[1]".to_string(), None, Some(json!({ "x": 2 }))),
+                (".b".to_string(), Some(json!(true)), None),
+                (".c".to_string(), None, Some(json!("foo"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_agrees_with_diff() {
+        let configs = vec![
+            Config::new(CompareMode::Inclusive),
+            Config::new(CompareMode::Strict),
+            Config::new(CompareMode::Strict).array_sorting_mode(ArraySortingMode::Ignore),
+            Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Ignore),
+            Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Subsequence),
+            Config::new(CompareMode::Strict).numeric_mode(NumericMode::AssumeFloat),
+            Config::new(CompareMode::Strict).ignore_path("..id"),
+            Config::new(CompareMode::Inclusive).enable_placeholders(),
+        ];
+        let values = vec![
+            json!(null),
+            json!(1),
+            json!(1.0),
+            json!("${any_number}"),
+            json!([1, 2, 3]),
+            json!([3, 2, 1]),
+            json!([1, 3]),
+            json!([[1, 2], [3]]),
+            json!([[3], [2, 1]]),
+            json!({ "id": 1, "a": [1, 2] }),
+            json!({ "id": 2, "a": [1, 2] }),
+            json!({ "id": 1, "a": [2, 1], "b": true }),
+            json!({ "a": "${any}" }),
+        ];
+
+        for config in &configs {
+            for lhs in &values {
+                for rhs in &values {
+                    assert_eq!(
+                        matches(lhs, rhs, config),
+                        diff(lhs, rhs, config).is_empty(),
+                        "lhs: {}, rhs: {}, config: {:?}",
+                        lhs,
+                        rhs,
+                        config
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_stops_at_first_difference() {
+        let mut acc = Differences::fail_fast();
+        let (lhs, rhs, config) = (
+            json!([1, 2, 3]),
+            json!([4, 5, 6]),
+            Config::new(CompareMode::Strict),
+        );
+        diff_with(&lhs, &rhs, &config, &mut PathStack::default(), &mut acc);
+        assert!(acc.is_done());
+        assert!(acc.found.is_empty());
+        assert_eq!(acc.len(), 1);
+    }
+
+    #[test]
+    fn test_path_stack_is_unwound() {
+        let lhs = json!({ "a": [{ "b": 1 }, [2, 3]], "c": { "d": null } });
+        let rhs = json!({ "a": [{ "b": 2 }, [3, 2]], "c": { "e": null } });
+
+        for sorting in [ArraySortingMode::Strict, ArraySortingMode::Ignore] {
+            let config = Config::new(CompareMode::Strict).array_sorting_mode(sorting);
+            let mut path = PathStack::default();
+            let mut acc = Differences::new(None);
+            diff_with(&lhs, &rhs, &config, &mut path, &mut acc);
+
+            assert!(path.keys().is_empty());
+            assert_eq!(acc.found[0].path().to_string(), ".a[0].b");
+        }
+    }
 }
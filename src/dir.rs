@@ -0,0 +1,401 @@
+use crate::core_ext::Indent;
+use crate::{compare_text, CompareMode, Config, Difference, JsonDiffError};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Compares two directories of `.json` files, such as a suite of golden files.
+///
+/// Files are matched by their path relative to each directory, including files in
+/// subdirectories, and each pair is compared like
+/// [`assert_json_text_matches_no_panic`](crate::assert_json_text_matches_no_panic). Files that
+/// are only in one of the directories are reported too. Files that don't end in `.json` are
+/// skipped, and symbolic links to directories aren't followed.
+///
+/// Every pair uses the same [`Config`] unless [`DirComparison::file_config`] gives a file its own.
+///
+/// ```
+/// use assert_json_diff::{CompareMode, Config, DirComparison, NumericMode};
+/// # let dir = std::env::temp_dir().join(format!("dir-comparison-doc-{}", std::process::id()));
+/// # for side in ["actual", "expected"] {
+/// #     std::fs::create_dir_all(dir.join(side).join("users")).unwrap();
+/// #     std::fs::write(dir.join(side).join("users/1.json"), r#"{ "id": 1 }"#).unwrap();
+/// # }
+/// # std::fs::write(dir.join("actual/stats.json"), r#"{ "mean": 1.0 }"#).unwrap();
+/// # std::fs::write(dir.join("expected/stats.json"), r#"{ "mean": 1 }"#).unwrap();
+/// # let (actual_dir, expected_dir) = (dir.join("actual"), dir.join("expected"));
+///
+/// let report = DirComparison::new(Config::new(CompareMode::Strict))
+///     .file_config(
+///         "stats.json",
+///         Config::new(CompareMode::Strict).numeric_mode(NumericMode::AssumeFloat),
+///     )
+///     .compare(actual_dir, expected_dir)
+///     .unwrap();
+///
+/// assert!(report.is_match());
+/// assert_eq!(report.compared(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirComparison {
+    config: Config,
+    file_configs: HashMap<PathBuf, Config>,
+}
+
+impl DirComparison {
+    /// Create a comparison that uses `config` for every pair of files.
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            file_configs: HashMap::new(),
+        }
+    }
+
+    /// Use a different [`Config`] for the file at `path`, relative to the directories being
+    /// compared.
+    pub fn file_config<P: AsRef<Path>>(mut self, path: P, config: Config) -> Self {
+        self.file_configs
+            .insert(path.as_ref().to_path_buf(), config);
+        self
+    }
+
+    /// Compare the `.json` files in two directories.
+    ///
+    /// Files that don't match, including ones that aren't valid JSON, are collected in the
+    /// returned [`DirReport`]. An error is only returned if a directory or file can't be read.
+    pub fn compare<Lhs, Rhs>(&self, lhs: Lhs, rhs: Rhs) -> Result<DirReport, JsonDiffError>
+    where
+        Lhs: AsRef<Path>,
+        Rhs: AsRef<Path>,
+    {
+        let (lhs, rhs) = (lhs.as_ref(), rhs.as_ref());
+        let lhs_files = json_files(lhs)?;
+        let rhs_files = json_files(rhs)?;
+
+        let mut report = DirReport {
+            inclusive: self.config.compare_mode == CompareMode::Inclusive,
+            compared: 0,
+            only_in_lhs: lhs_files.difference(&rhs_files).cloned().collect(),
+            only_in_rhs: rhs_files.difference(&lhs_files).cloned().collect(),
+            failures: Vec::new(),
+        };
+
+        for file in lhs_files.intersection(&rhs_files) {
+            let lhs_text = read(&lhs.join(file))?;
+            let rhs_text = read(&rhs.join(file))?;
+            let config = self.file_configs.get(file).unwrap_or(&self.config);
+
+            report.compared += 1;
+            if let Err(error) = compare_text(&lhs_text, &rhs_text, config) {
+                report.failures.push((file.clone(), error));
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Compare the `.json` files in two directories using the same [`Config`] for every file.
+///
+/// This is a shorthand for [`DirComparison::new(config).compare(lhs, rhs)`](DirComparison).
+pub fn diff_dirs<Lhs, Rhs>(lhs: Lhs, rhs: Rhs, config: Config) -> Result<DirReport, JsonDiffError>
+where
+    Lhs: AsRef<Path>,
+    Rhs: AsRef<Path>,
+{
+    DirComparison::new(config).compare(lhs, rhs)
+}
+
+/// The result of comparing two directories with [`DirComparison`].
+///
+/// Paths are relative to the directories and sorted. The `Display` implementation gives a report
+/// of every file that doesn't match, with the differences of each file indented below it. Paths
+/// are shown with the platform's separator, so `users/2.json` below is `users\2.json` on Windows:
+///
+/// ```text
+/// 2 of 3 JSON files don't match
+///
+/// only in lhs:
+///     users/2.json
+///
+/// users/1.json:
+///     json atoms at path ".name" (line 3 of lhs) are not equal:
+///         lhs:
+///             "Bob"
+///         rhs:
+///             "Alice"
+/// ```
+#[derive(Debug)]
+pub struct DirReport {
+    inclusive: bool,
+    compared: usize,
+    only_in_lhs: Vec<PathBuf>,
+    only_in_rhs: Vec<PathBuf>,
+    failures: Vec<(PathBuf, JsonDiffError)>,
+}
+
+impl DirReport {
+    /// Whether both directories have the same files and every pair matches.
+    pub fn is_match(&self) -> bool {
+        self.only_in_lhs.is_empty() && self.only_in_rhs.is_empty() && self.failures.is_empty()
+    }
+
+    /// The number of files that were in both directories and compared.
+    pub fn compared(&self) -> usize {
+        self.compared
+    }
+
+    /// Files that are only in the left hand side directory, or "actual" when using
+    /// [`CompareMode::Inclusive`].
+    pub fn only_in_lhs(&self) -> &[PathBuf] {
+        &self.only_in_lhs
+    }
+
+    /// Files that are only in the right hand side directory, or "expected" when using
+    /// [`CompareMode::Inclusive`].
+    pub fn only_in_rhs(&self) -> &[PathBuf] {
+        &self.only_in_rhs
+    }
+
+    /// The files that are in both directories but don't match, along with why.
+    ///
+    /// The error is a [`JsonDiffError::Mismatch`] if both files are valid JSON, and
+    /// [`JsonDiffError::LhsParse`] or [`JsonDiffError::RhsParse`] otherwise.
+    pub fn failures(&self) -> &[(PathBuf, JsonDiffError)] {
+        &self.failures
+    }
+
+    /// The differences of every file that doesn't match, ordered by file and then as described in
+    /// [`diff_json`](crate::diff_json).
+    pub fn differences(&self) -> impl Iterator<Item = (&Path, &Difference)> {
+        self.failures.iter().flat_map(|(file, error)| {
+            let differences = match error {
+                JsonDiffError::Mismatch(mismatch) => mismatch.differences(),
+                _ => &[],
+            };
+            differences
+                .iter()
+                .map(move |difference| (file.as_path(), difference))
+        })
+    }
+
+    fn failure_count(&self) -> usize {
+        self.only_in_lhs.len() + self.only_in_rhs.len() + self.failures.len()
+    }
+}
+
+impl fmt::Display for DirReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.compared + self.only_in_lhs.len() + self.only_in_rhs.len();
+        if self.is_match() {
+            return write!(f, "all {} JSON files match", total);
+        }
+        write!(
+            f,
+            "{} of {} JSON files don't match",
+            self.failure_count(),
+            total
+        )?;
+
+        let (lhs, rhs) = if self.inclusive {
+            ("actual", "expected")
+        } else {
+            ("lhs", "rhs")
+        };
+        for (side, files) in [(lhs, &self.only_in_lhs), (rhs, &self.only_in_rhs)] {
+            if !files.is_empty() {
+                write!(f, "\n\nonly in {}:", side)?;
+                for file in files {
+                    write!(f, "\n    {}", file.display())?;
+                }
+            }
+        }
+        for (file, error) in &self.failures {
+            write!(f, "\n\n{}:\n{}", file.display(), error.indent(4))?;
+        }
+        Ok(())
+    }
+}
+
+/// The paths of the `.json` files in `root` and its subdirectories, relative to `root`.
+fn json_files(root: &Path) -> Result<BTreeSet<PathBuf>, JsonDiffError> {
+    let mut files = BTreeSet::new();
+    collect_json_files(root, Path::new(""), &mut files)?;
+    Ok(files)
+}
+
+fn collect_json_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut BTreeSet<PathBuf>,
+) -> Result<(), JsonDiffError> {
+    // joining an empty path would add a trailing separator to error messages
+    let path = if dir.as_os_str().is_empty() {
+        root.to_path_buf()
+    } else {
+        root.join(dir)
+    };
+    let io_error = |error| JsonDiffError::Io(path.clone(), error);
+
+    for entry in fs::read_dir(&path).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        let relative = dir.join(entry.file_name());
+        // `file_type` doesn't follow symlinks, so a link back to a parent can't loop forever
+        if entry.file_type().map_err(io_error)?.is_dir() {
+            collect_json_files(root, &relative, files)?;
+        } else if relative.extension().is_some_and(|ext| ext == "json") {
+            files.insert(relative);
+        }
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, JsonDiffError> {
+    fs::read(path).map_err(|error| JsonDiffError::Io(path.to_path_buf(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NumericMode;
+
+    fn fixtures(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("assert-json-diff-dir-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_dir_all(&dir);
+        for (path, contents) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_matching_dirs() {
+        let lhs = fixtures(
+            "matching-lhs",
+            &[
+                ("a.json", "[1]"),
+                ("nested/b.json", "{}"),
+                ("notes.txt", "x"),
+            ],
+        );
+        let rhs = fixtures(
+            "matching-rhs",
+            &[("a.json", "[1]"), ("nested/b.json", "{}")],
+        );
+
+        let report = diff_dirs(&lhs, &rhs, Config::new(CompareMode::Strict)).unwrap();
+        assert!(report.is_match());
+        assert_eq!(report.compared(), 2);
+        assert_eq!(report.to_string(), "all 2 JSON files match");
+    }
+
+    #[test]
+    fn test_report() {
+        let lhs = fixtures(
+            "report-lhs",
+            &[
+                ("users/1.json", "{\n  \"id\": 1,\n  \"name\": \"Bob\"\n}"),
+                ("users/2.json", "{}"),
+                ("same.json", "true"),
+                ("invalid.json", "{"),
+            ],
+        );
+        let rhs = fixtures(
+            "report-rhs",
+            &[
+                ("users/1.json", r#"{ "id": 1, "name": "Alice" }"#),
+                ("users/3.json", "{}"),
+                ("same.json", "true"),
+                ("invalid.json", "{}"),
+            ],
+        );
+
+        // paths are displayed with the platform's separator
+        let user = |file| Path::new("users").join(file);
+
+        let report = diff_dirs(&lhs, &rhs, Config::new(CompareMode::Strict)).unwrap();
+        assert!(!report.is_match());
+        assert_eq!(report.compared(), 3);
+        assert_eq!(report.only_in_lhs(), &[user("2.json")]);
+        assert_eq!(report.only_in_rhs(), &[user("3.json")]);
+        assert_eq!(
+            report
+                .differences()
+                .map(|(file, difference)| (file.to_path_buf(), difference.path().to_string()))
+                .collect::<Vec<_>>(),
+            vec![(user("1.json"), ".name".to_string())]
+        );
+        assert_eq!(
+            report.to_string(),
+            format!(
+                r#"4 of 5 JSON files don't match
+
+only in lhs:
+    {}
+
+only in rhs:
+    {}
+
+invalid.json:
+    Couldn't parse left hand side as JSON: EOF while parsing an object at line 1 column 1
+
+        1 | {{
+          | ^
+
+{}:
+    json atoms at path ".name" (line 3 of lhs) are not equal:
+        lhs:
+            "Bob"
+        rhs:
+            "Alice""#,
+                user("2.json").display(),
+                user("3.json").display(),
+                user("1.json").display(),
+            )
+        );
+    }
+
+    #[test]
+    fn test_file_configs() {
+        let lhs = fixtures("configs-lhs", &[("a.json", "1.0"), ("b.json", "1.0")]);
+        let rhs = fixtures("configs-rhs", &[("a.json", "1"), ("b.json", "1")]);
+
+        let report = DirComparison::new(Config::new(CompareMode::Strict))
+            .file_config(
+                "a.json",
+                Config::new(CompareMode::Strict).numeric_mode(NumericMode::AssumeFloat),
+            )
+            .compare(&lhs, &rhs)
+            .unwrap();
+        let failures = report
+            .failures()
+            .iter()
+            .map(|(file, _)| file.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(failures, vec![PathBuf::from("b.json")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_dirs_arent_followed() {
+        let dir = fixtures("symlinks", &[("nested/a.json", "{}")]);
+        std::os::unix::fs::symlink(&dir, dir.join("nested/loop")).unwrap();
+
+        let report = diff_dirs(&dir, &dir, Config::new(CompareMode::Strict)).unwrap();
+        assert!(report.is_match());
+        assert_eq!(report.compared(), 1);
+    }
+
+    #[test]
+    fn test_missing_dir() {
+        let lhs = fixtures("missing-lhs", &[]);
+        let error = diff_dirs(&lhs, &lhs, Config::new(CompareMode::Strict)).unwrap_err();
+        assert!(matches!(error, JsonDiffError::Io(path, _) if path == lhs));
+    }
+}
@@ -0,0 +1,354 @@
+use crate::diff::Difference;
+use std::{error::Error, fmt, io, path::PathBuf};
+
+/// The error returned by [`assert_json_matches_no_panic`](crate::assert_json_matches_no_panic).
+///
+/// Its `Display` implementation gives the message that
+/// [`assert_json_matches`](macro.assert_json_matches.html) panics with.
+///
+/// ```
+/// use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config, JsonDiffError};
+/// use serde_json::json;
+///
+/// let error = assert_json_matches_no_panic(
+///     &json!({ "a": 1 }),
+///     &json!({ "a": 2 }),
+///     Config::new(CompareMode::Strict),
+/// )
+/// .unwrap_err();
+///
+/// match error {
+///     JsonDiffError::Mismatch(mismatch) => assert_eq!(mismatch.differences().len(), 1),
+///     other => panic!("unexpected error: {}", other),
+/// }
+/// ```
+///
+/// More variants may be added in the future, so matches need a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JsonDiffError {
+    /// The left hand side couldn't be converted to JSON.
+    LhsSerialization(serde_json::Error),
+    /// The right hand side couldn't be converted to JSON.
+    RhsSerialization(serde_json::Error),
+    /// The left hand side JSON text isn't valid JSON.
+    LhsParse(ParseError),
+    /// The right hand side JSON text isn't valid JSON.
+    RhsParse(ParseError),
+    /// A snapshot file couldn't be read or written.
+    SnapshotIo(PathBuf, io::Error),
+    /// The left hand side YAML text couldn't be converted to JSON.
+    ///
+    /// Only exists with the `yaml` feature. Since the enum is `#[non_exhaustive]`, enabling the
+    /// feature doesn't break existing matches.
+    #[cfg(feature = "yaml")]
+    LhsYaml(YamlError),
+    /// The right hand side YAML text couldn't be converted to JSON.
+    ///
+    /// Only exists with the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    RhsYaml(YamlError),
+    /// A file or directory being compared couldn't be read.
+    Io(PathBuf, io::Error),
+    /// The values were converted to JSON but don't match.
+    Mismatch(Mismatch),
+}
+
+impl fmt::Display for JsonDiffError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonDiffError::LhsSerialization(err) => write!(
+                f,
+                "Couldn't convert left hand side value to JSON. Serde error: {}",
+                err
+            ),
+            JsonDiffError::RhsSerialization(err) => write!(
+                f,
+                "Couldn't convert right hand side value to JSON. Serde error: {}",
+                err
+            ),
+            JsonDiffError::LhsParse(err) => {
+                write!(f, "Couldn't parse left hand side as JSON: {}", err)
+            }
+            JsonDiffError::RhsParse(err) => {
+                write!(f, "Couldn't parse right hand side as JSON: {}", err)
+            }
+            JsonDiffError::SnapshotIo(path, err) => {
+                write!(f, "Couldn't access snapshot {}: {}", path.display(), err)
+            }
+            JsonDiffError::Io(path, err) => write!(f, "Couldn't read {}: {}", path.display(), err),
+            #[cfg(feature = "yaml")]
+            JsonDiffError::LhsYaml(err) => {
+                write!(f, "Couldn't parse left hand side as YAML: {}", err)
+            }
+            #[cfg(feature = "yaml")]
+            JsonDiffError::RhsYaml(err) => {
+                write!(f, "Couldn't parse right hand side as YAML: {}", err)
+            }
+            JsonDiffError::Mismatch(mismatch) => write!(f, "{}", mismatch),
+        }
+    }
+}
+
+impl Error for JsonDiffError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonDiffError::LhsSerialization(err) | JsonDiffError::RhsSerialization(err) => {
+                Some(err)
+            }
+            JsonDiffError::LhsParse(err) | JsonDiffError::RhsParse(err) => Some(err),
+            JsonDiffError::SnapshotIo(_, err) | JsonDiffError::Io(_, err) => Some(err),
+            #[cfg(feature = "yaml")]
+            JsonDiffError::LhsYaml(err) | JsonDiffError::RhsYaml(err) => Some(err),
+            JsonDiffError::Mismatch(_) => None,
+        }
+    }
+}
+
+/// Two JSON values that don't match, along with the differences between them.
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    differences: Vec<Difference>,
+    omitted: usize,
+    message: String,
+}
+
+impl Mismatch {
+    pub(crate) fn new(differences: Vec<Difference>, omitted: usize, message: String) -> Self {
+        Self {
+            differences,
+            omitted,
+            message,
+        }
+    }
+
+    /// Add a note to the end of the message.
+    pub(crate) fn with_note(mut self, note: &str) -> Self {
+        self.message.push_str("\n\n");
+        self.message.push_str(note);
+        self
+    }
+
+    /// The differences, in the order described in [`diff_json`](crate::diff_json).
+    pub fn differences(&self) -> &[Difference] {
+        &self.differences
+    }
+
+    /// The number of differences beyond [`Config::max_differences`] that weren't collected.
+    ///
+    /// [`Config::max_differences`]: crate::Config::max_differences
+    pub fn omitted(&self) -> usize {
+        self.omitted
+    }
+
+    /// Take ownership of the differences.
+    pub fn into_differences(self) -> Vec<Difference> {
+        self.differences
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for Mismatch {}
+
+/// An invalid path pattern given to [`Config::try_ignore_path`](crate::Config::try_ignore_path).
+#[derive(Debug, Clone)]
+pub struct PathPatternError {
+    pattern: String,
+    reason: String,
+}
+
+impl PathPatternError {
+    pub(crate) fn new(pattern: &str, reason: String) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for PathPatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid path pattern `{}`: {}",
+            self.pattern, self.reason
+        )
+    }
+}
+
+impl Error for PathPatternError {}
+
+/// An invalid tolerance given to [`Config::try_numeric_mode`](crate::Config::try_numeric_mode).
+///
+/// Tolerances must be finite and not negative.
+#[derive(Debug, Copy, Clone)]
+pub struct ToleranceError {
+    tolerance: f64,
+}
+
+impl ToleranceError {
+    pub(crate) fn new(tolerance: f64) -> Self {
+        Self { tolerance }
+    }
+}
+
+impl fmt::Display for ToleranceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid tolerance `{}`: tolerances must be finite and not negative",
+            self.tolerance
+        )
+    }
+}
+
+impl Error for ToleranceError {}
+
+/// JSON text that couldn't be parsed.
+///
+/// The `Display` implementation shows where the error is, along with the offending line:
+///
+/// ```text
+/// expected `,` or `}` at line 3 column 3
+///
+///     3 |   "b": 2
+///       |   ^
+/// ```
+#[derive(Debug)]
+pub struct ParseError {
+    error: serde_json::Error,
+    source_line: Option<String>,
+}
+
+impl ParseError {
+    pub(crate) fn new(error: serde_json::Error, text: &[u8]) -> Self {
+        let source_line = source_line(text, error.line());
+        Self { error, source_line }
+    }
+
+    /// The line where the error is, starting from 1.
+    pub fn line(&self) -> usize {
+        self.error.line()
+    }
+
+    /// The column where the error is, counted in bytes from 1.
+    pub fn column(&self) -> usize {
+        self.error.column()
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        write_source_line(f, self.line(), self.column(), self.source_line.as_deref())
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// YAML text that couldn't be converted to JSON, either because it isn't valid YAML or because it
+/// uses something JSON has no equivalent for. See [`yaml_to_json`](crate::yaml_to_json).
+///
+/// Like [`ParseError`], the `Display` implementation includes the offending line.
+#[cfg(feature = "yaml")]
+#[derive(Debug)]
+pub struct YamlError {
+    message: String,
+    line: usize,
+    column: usize,
+    source_line: Option<String>,
+}
+
+#[cfg(feature = "yaml")]
+impl YamlError {
+    pub(crate) fn new(message: &str, line: usize, column: usize, text: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            line,
+            column,
+            source_line: source_line(text.as_bytes(), line),
+        }
+    }
+
+    /// The line where the error is, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column where the error is, counted in characters from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message, self.line, self.column
+        )?;
+        let byte_column = self.source_line.as_deref().and_then(|line| {
+            line.char_indices()
+                .map(|(idx, _)| idx)
+                .chain(Some(line.len()))
+                .nth(self.column - 1)
+        });
+        write_source_line(
+            f,
+            self.line,
+            byte_column.map_or(self.column, |idx| idx + 1),
+            self.source_line.as_deref(),
+        )
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl Error for YamlError {}
+
+/// Line `line` of `text`, counting from 1, without trailing whitespace.
+fn source_line(text: &[u8], line: usize) -> Option<String> {
+    line.checked_sub(1)
+        .and_then(|idx| text.split(|byte| *byte == b'\n').nth(idx))
+        .map(|line| String::from_utf8_lossy(line).trim_end().to_string())
+}
+
+/// Write the offending line with a caret under `column`, which is counted in bytes from 1.
+fn write_source_line(
+    f: &mut fmt::Formatter,
+    line: usize,
+    column: usize,
+    source_line: Option<&str>,
+) -> fmt::Result {
+    let source_line = match source_line {
+        Some(source_line) => source_line,
+        None => return Ok(()),
+    };
+
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+    // keep tabs so the caret lines up with the offending character
+    let prefix = source_line
+        .as_bytes()
+        .get(..column.saturating_sub(1))
+        .unwrap_or(source_line.as_bytes());
+    let pad = String::from_utf8_lossy(prefix)
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    write!(
+        f,
+        "\n\n    {} | {}\n    {} | {}^",
+        number, source_line, gutter, pad
+    )
+}
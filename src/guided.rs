@@ -0,0 +1,679 @@
+use crate::diff::{KeyRef, PathStack};
+use crate::{ArraySortingMode, Config};
+use serde::ser::{self, Error as _, Serialize};
+use serde_json::{Error, Map, Value};
+
+/// Serialize `value` to JSON, skipping the parts that comparing it against `shape` with
+/// [`CompareMode::Inclusive`](crate::CompareMode::Inclusive) wouldn't look at.
+///
+/// Object fields that aren't in the corresponding object in `shape` are skipped, as are array
+/// elements beyond the length of the corresponding array when using
+/// [`ArraySortingMode::Strict`]. Everything else is serialized like `serde_json::to_value`, as are
+/// values found at a path with a [`Matcher`](crate::Matcher) since the matcher might look at all of
+/// it.
+///
+/// Skipped values aren't serialized at all, so they can't cause serialization errors.
+pub(crate) fn to_value<T>(value: &T, shape: &Value, config: &Config) -> Result<Value, Error>
+where
+    T: Serialize + ?Sized,
+{
+    guide(value, Some(shape), config, &mut PathStack::default())
+}
+
+/// Serialize `value`, guided by `shape` if it is a container.
+fn guide<'a, T>(
+    value: &T,
+    shape: Option<&'a Value>,
+    config: &'a Config,
+    path: &mut PathStack<'a>,
+) -> Result<Value, Error>
+where
+    T: Serialize + ?Sized,
+{
+    match shape {
+        Some(shape @ Value::Object(_)) | Some(shape @ Value::Array(_))
+            if config.path_matcher(path.keys()).is_none() =>
+        {
+            value.serialize(Guided {
+                shape,
+                config,
+                path,
+            })
+        }
+        _ => serde_json::to_value(value),
+    }
+}
+
+/// serde_json serializes some of its own types, such as `RawValue` and, with the
+/// `arbitrary_precision` feature, `Number`, as structs with a private name starting with this.
+/// Only serde_json's own serializer knows how to turn them back into values.
+const SERDE_JSON_PRIVATE: &str = "$serde_json::private::";
+
+struct Guided<'a, 'p> {
+    shape: &'a Value,
+    config: &'a Config,
+    path: &'p mut PathStack<'a>,
+}
+
+impl<'a, 'p> Guided<'a, 'p> {
+    /// The shape of the content of an enum variant, which serde_json wraps as
+    /// `{ "variant": content }`.
+    ///
+    /// The variant is pushed onto the path if it has a shape, and must be popped once the content
+    /// has been serialized.
+    fn enter(&mut self, variant: Option<&'static str>) -> Option<&'a Value> {
+        let variant = match variant {
+            Some(variant) => variant,
+            None => return Some(self.shape),
+        };
+        let (key, shape) = self.shape.as_object()?.get_key_value(variant)?;
+        self.path.push(KeyRef::Field(key));
+        Some(shape)
+    }
+
+    fn seq(mut self, variant: Option<&'static str>, len: Option<usize>) -> GuidedSeq<'a, 'p> {
+        let shape = self.enter(variant);
+        let items = match (shape, self.config.array_sorting_mode) {
+            (Some(Value::Array(items)), ArraySortingMode::Strict) => Some(items.as_slice()),
+            _ => None,
+        };
+        let len = len.unwrap_or(0);
+        GuidedSeq {
+            items,
+            config: self.config,
+            path: self.path,
+            variant,
+            entered: variant.is_some() && shape.is_some(),
+            next_idx: 0,
+            vec: Vec::with_capacity(items.map_or(len, |items| items.len().min(len))),
+        }
+    }
+
+    fn map(mut self, variant: Option<&'static str>) -> GuidedMap<'a, 'p> {
+        let shape = self.enter(variant);
+        GuidedMap {
+            fields: shape.and_then(Value::as_object),
+            config: self.config,
+            path: self.path,
+            variant,
+            entered: variant.is_some() && shape.is_some(),
+            next_key: None,
+            map: Map::new(),
+        }
+    }
+}
+
+/// Wrap the content of an enum variant like serde_json does, as `{ "variant": content }`.
+fn wrap(variant: Option<&'static str>, content: Value) -> Value {
+    match variant {
+        Some(variant) => {
+            let mut map = Map::new();
+            map.insert(variant.to_string(), content);
+            Value::Object(map)
+        }
+        None => content,
+    }
+}
+
+macro_rules! delegate {
+    ($($name:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $name(self, v: $ty) -> Result<Value, Error> {
+                serde_json::value::Serializer.$name(v)
+            }
+        )*
+    };
+}
+
+impl<'a, 'p> ser::Serializer for Guided<'a, 'p> {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = GuidedSeq<'a, 'p>;
+    type SerializeTuple = GuidedSeq<'a, 'p>;
+    type SerializeTupleStruct = GuidedSeq<'a, 'p>;
+    type SerializeTupleVariant = GuidedSeq<'a, 'p>;
+    type SerializeMap = GuidedMap<'a, 'p>;
+    type SerializeStruct = GuidedStruct<'a, 'p>;
+    type SerializeStructVariant = GuidedMap<'a, 'p>;
+
+    delegate!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    );
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        if name.starts_with(SERDE_JSON_PRIVATE) {
+            return serde_json::value::Serializer.serialize_newtype_struct(name, value);
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        let content = match self.enter(Some(variant)) {
+            Some(shape) => {
+                let content = guide(value, Some(shape), self.config, self.path);
+                self.path.pop();
+                content?
+            }
+            None => serde_json::to_value(value)?,
+        };
+        Ok(wrap(Some(variant), content))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Ok(self.seq(None, len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        Ok(self.seq(None, Some(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Ok(self.seq(None, Some(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Ok(self.seq(Some(variant), Some(len)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(self.map(None))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        if name.starts_with(SERDE_JSON_PRIVATE) {
+            let json = serde_json::value::Serializer.serialize_struct(name, len)?;
+            return Ok(GuidedStruct::Json(json));
+        }
+        Ok(GuidedStruct::Guided(self.map(None)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Ok(self.map(Some(variant)))
+    }
+}
+
+/// Serializes arrays, keeping only the elements that have a counterpart in `items`.
+///
+/// If `items` is `None` every element is serialized.
+struct GuidedSeq<'a, 'p> {
+    items: Option<&'a [Value]>,
+    config: &'a Config,
+    path: &'p mut PathStack<'a>,
+    variant: Option<&'static str>,
+    entered: bool,
+    next_idx: usize,
+    vec: Vec<Value>,
+}
+
+impl GuidedSeq<'_, '_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let idx = self.next_idx;
+        self.next_idx += 1;
+
+        let value = match self.items {
+            Some(items) => match items.get(idx) {
+                Some(shape) => {
+                    self.path.push(KeyRef::Idx(idx));
+                    let value = guide(value, Some(shape), self.config, self.path);
+                    self.path.pop();
+                    value?
+                }
+                None => return Ok(()),
+            },
+            None => serde_json::to_value(value)?,
+        };
+        self.vec.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        if self.entered {
+            self.path.pop();
+        }
+        wrap(self.variant, Value::Array(self.vec))
+    }
+}
+
+impl ser::SerializeSeq for GuidedSeq<'_, '_> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for GuidedSeq<'_, '_> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for GuidedSeq<'_, '_> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for GuidedSeq<'_, '_> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Serializes objects, keeping only the fields that are also in `fields`.
+///
+/// If `fields` is `None` every field is serialized.
+struct GuidedMap<'a, 'p> {
+    fields: Option<&'a Map<String, Value>>,
+    config: &'a Config,
+    path: &'p mut PathStack<'a>,
+    variant: Option<&'static str>,
+    entered: bool,
+    next_key: Option<String>,
+    map: Map<String, Value>,
+}
+
+impl GuidedMap<'_, '_> {
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        let value = match self.fields {
+            Some(fields) => match fields.get_key_value(key) {
+                Some((key, shape)) => {
+                    self.path.push(KeyRef::Field(key));
+                    let value = guide(value, Some(shape), self.config, self.path);
+                    self.path.pop();
+                    value?
+                }
+                None => return Ok(()),
+            },
+            None => serde_json::to_value(value)?,
+        };
+        self.map.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        if self.entered {
+            self.path.pop();
+        }
+        wrap(self.variant, Value::Object(self.map))
+    }
+}
+
+impl ser::SerializeMap for GuidedMap<'_, '_> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        // keys are converted to strings the same way serde_json does it
+        let key = match serde_json::to_value(key)? {
+            Value::String(key) => key,
+            Value::Number(key) => key.to_string(),
+            Value::Bool(key) => key.to_string(),
+            _ => return Err(Error::custom("key must be a string")),
+        };
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .next_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Serializes structs like [`GuidedMap`], except for serde_json's private structs which are
+/// serialized by serde_json.
+enum GuidedStruct<'a, 'p> {
+    Guided(GuidedMap<'a, 'p>),
+    Json(<serde_json::value::Serializer as ser::Serializer>::SerializeStruct),
+}
+
+impl ser::SerializeStruct for GuidedStruct<'_, '_> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        match self {
+            GuidedStruct::Guided(map) => map.field(key, value),
+            GuidedStruct::Json(json) => json.serialize_field(key, value),
+        }
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        match self {
+            GuidedStruct::Guided(map) => Ok(map.finish()),
+            GuidedStruct::Json(json) => json.end(),
+        }
+    }
+}
+
+impl ser::SerializeStructVariant for GuidedMap<'_, '_> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CompareMode;
+    use serde::ser::Serializer;
+    use serde::Serialize;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn guided<T: Serialize>(value: &T, shape: Value) -> Value {
+        to_value(value, &shape, &Config::new(CompareMode::Inclusive)).unwrap()
+    }
+
+    #[derive(Serialize)]
+    struct User {
+        id: u32,
+        name: String,
+        tags: Vec<&'static str>,
+        address: Address,
+    }
+
+    #[derive(Serialize)]
+    struct Address {
+        city: &'static str,
+        zip: &'static str,
+    }
+
+    fn user() -> User {
+        User {
+            id: 1,
+            name: "Bob".to_string(),
+            tags: vec!["a", "b", "c"],
+            address: Address {
+                city: "Copenhagen",
+                zip: "1000",
+            },
+        }
+    }
+
+    #[test]
+    fn test_skips_fields_that_arent_expected() {
+        assert_eq!(guided(&user(), json!({ "id": 2 })), json!({ "id": 1 }));
+        assert_eq!(
+            guided(&user(), json!({ "address": { "city": "Aarhus" } })),
+            json!({ "address": { "city": "Copenhagen" } })
+        );
+        assert_eq!(guided(&user(), json!({ "missing": 1 })), json!({}));
+    }
+
+    #[test]
+    fn test_values_that_arent_containers_are_serialized_fully() {
+        assert_eq!(
+            guided(&user(), json!({ "address": 1 })),
+            json!({ "address": { "city": "Copenhagen", "zip": "1000" } })
+        );
+        assert_eq!(
+            guided(&user(), json!("${any}")),
+            serde_json::to_value(user()).unwrap()
+        );
+        assert_eq!(
+            guided(&user(), json!({ "tags": {} })),
+            json!({ "tags": ["a", "b", "c"] })
+        );
+    }
+
+    #[test]
+    fn test_arrays() {
+        assert_eq!(
+            guided(&user(), json!({ "tags": ["x"] })),
+            json!({ "tags": ["a"] })
+        );
+        assert_eq!(
+            guided(&vec![user(), user()], json!([{ "id": 1 }])),
+            json!([{ "id": 1 }])
+        );
+        assert_eq!(guided(&(1, "a", true), json!([0, "b"])), json!([1, "a"]));
+
+        // any element might be compared with any expected element when the order is ignored
+        let config =
+            Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Ignore);
+        assert_eq!(
+            to_value(&vec![1, 2, 3], &json!([3]), &config).unwrap(),
+            json!([1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn test_maps_and_enums() {
+        let mut map = BTreeMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+        assert_eq!(guided(&map, json!({ "2": "x" })), json!({ "2": "b" }));
+
+        #[derive(Serialize)]
+        enum Event {
+            Unit,
+            Created(User),
+            Moved { from: Address, to: Address },
+            Pair(u32, u32),
+        }
+
+        assert_eq!(guided(&Event::Unit, json!({})), json!("Unit"));
+        assert_eq!(
+            guided(&Event::Created(user()), json!({ "Created": { "id": 1 } })),
+            json!({ "Created": { "id": 1 } })
+        );
+        let moved = Event::Moved {
+            from: user().address,
+            to: user().address,
+        };
+        assert_eq!(
+            guided(&moved, json!({ "Moved": { "to": { "zip": "1" } } })),
+            json!({ "Moved": { "to": { "zip": "1000" } } })
+        );
+        assert_eq!(
+            guided(&Event::Pair(1, 2), json!({ "Pair": [1] })),
+            json!({ "Pair": [1] })
+        );
+    }
+
+    #[test]
+    fn test_values_with_matchers_are_serialized_fully() {
+        let config = Config::new(CompareMode::Inclusive).matcher_at(".tags", |_: &Value| Ok(()));
+        assert_eq!(
+            to_value(&user(), &json!({ "tags": [] }), &config).unwrap(),
+            json!({ "tags": ["a", "b", "c"] })
+        );
+
+        #[derive(Serialize)]
+        enum Event {
+            Tagged { tags: Vec<u32> },
+        }
+
+        let config =
+            Config::new(CompareMode::Inclusive).matcher_at(".Tagged.tags", |_: &Value| Ok(()));
+        assert_eq!(
+            to_value(
+                &Event::Tagged { tags: vec![1, 2] },
+                &json!({ "Tagged": { "tags": [] } }),
+                &config
+            )
+            .unwrap(),
+            json!({ "Tagged": { "tags": [1, 2] } })
+        );
+    }
+
+    #[test]
+    fn test_raw_values_are_serialized_by_serde_json() {
+        use serde_json::value::RawValue;
+
+        #[derive(Serialize)]
+        struct Response {
+            body: Box<RawValue>,
+        }
+
+        let response = Response {
+            body: RawValue::from_string(r#"{ "id": 1, "name": "Bob" }"#.to_string()).unwrap(),
+        };
+        assert_eq!(
+            guided(&response, json!({ "body": { "id": 1 } })),
+            json!({ "body": { "id": 1, "name": "Bob" } })
+        );
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_arbitrary_precision_numbers_are_serialized_by_serde_json() {
+        #[derive(Serialize)]
+        struct Stats {
+            total: serde_json::Number,
+        }
+
+        let stats = Stats {
+            total: "123456789012345678901234567890".parse().unwrap(),
+        };
+        assert_eq!(
+            guided(&stats, json!({ "total": {} })),
+            json!({
+                "total": serde_json::from_str::<Value>("123456789012345678901234567890").unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_skipped_values_arent_serialized() {
+        struct Failing;
+
+        impl Serialize for Failing {
+            fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+                Err(ser::Error::custom("can't serialize this"))
+            }
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert("ok", None);
+        map.insert("failing", Some(Failing));
+
+        assert_eq!(guided(&map, json!({ "ok": null })), json!({ "ok": null }));
+        assert!(to_value(
+            &map,
+            &json!({ "failing": null }),
+            &Config::new(CompareMode::Inclusive)
+        )
+        .is_err());
+    }
+}
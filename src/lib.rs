@@ -111,7 +111,25 @@
 //! That will print
 //!
 //! ```text
-//! json atom at path ".a.b" is missing from actual
+//! json atom at path ".a.b" is missing from actual:
+//!     expected:
+//!         1
+//! ```
+//!
+//! Arrays are compared index by index, so `expected` element `i` must be included in `actual`
+//! element `i`. To instead check that each expected element is included *somewhere* in the actual
+//! array use [`ArraySortingMode::Ignore`], or [`ArraySortingMode::Subsequence`] if the elements
+//! must also appear in the same order:
+//!
+//! ```
+//! use assert_json_diff::{assert_json_matches, ArraySortingMode, CompareMode, Config};
+//! use serde_json::json;
+//!
+//! assert_json_matches!(
+//!     json!({ "users": [{ "id": 3 }, { "id": 5, "name": "Bob" }] }),
+//!     json!({ "users": [{ "id": 5 }] }),
+//!     Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Ignore),
+//! )
 //! ```
 //!
 //! ## Exact matching
@@ -131,12 +149,112 @@
 //! This will panic with the error message:
 //!
 //! ```text
-//! json atom at path ".a.b" is missing from lhs
+//! json atom at path ".a.b" is missing from lhs:
+//!     rhs:
+//!         1
 //! ```
 //!
 //! ## Further customization
 //!
 //! You can use [`assert_json_matches`] to further customize the comparison.
+//!
+//! ## Comparing JSON text
+//!
+//! If you have JSON text, such as the body of an HTTP response, you don't have to parse it first.
+//! [`assert_json_text_include`], [`assert_json_text_eq`], and [`assert_json_text_matches`] take
+//! anything that implements `AsRef<[u8]>`, report invalid JSON with the line and column of the
+//! problem, and include the line of each difference in the failure message:
+//!
+//! ```should_panic
+//! use assert_json_diff::assert_json_text_include;
+//!
+//! let body = r#"{
+//!     "id": 1,
+//!     "name": "Bob"
+//! }"#;
+//!
+//! assert_json_text_include!(actual: body, expected: r#"{ "name": "Alice" }"#);
+//! ```
+//!
+//! This will panic with the error message:
+//!
+//! ```text
+//! json atoms at path ".name" (line 3 of actual) are not equal:
+//!     expected:
+//!         "Alice"
+//!     actual:
+//!         "Bob"
+//! ```
+//!
+//! ## Inspecting differences
+//!
+//! If you need more than an error message, [`diff_json`] returns the differences as a list of
+//! [`Difference`]s that can be filtered or rendered however you like:
+//!
+//! ```
+//! use assert_json_diff::{diff_json, CompareMode, Config, DifferenceKind, Key};
+//! use serde_json::json;
+//!
+//! let diffs = diff_json(
+//!     &json!({ "a": { "b": 1 } }),
+//!     &json!({ "a": { "b": "1" } }),
+//!     &Config::new(CompareMode::Strict),
+//! );
+//!
+//! assert_eq!(diffs.len(), 1);
+//! assert_eq!(diffs[0].kind(), &DifferenceKind::TypeMismatch);
+//! assert_eq!(diffs[0].path().keys(), &[Key::Field("a".to_string()), Key::Field("b".to_string())]);
+//! assert_eq!(diffs[0].lhs(), Some(&json!(1)));
+//! assert_eq!(diffs[0].rhs(), Some(&json!("1")));
+//! ```
+//!
+//! ## Comparing directories
+//!
+//! [`diff_dirs`] compares two directories of `.json` files, such as a suite of golden files.
+//! Files are matched by their relative paths and each pair is compared with the same `Config`, or a
+//! per-file one given to [`DirComparison::file_config`]. The returned [`DirReport`] lists files that
+//! are only on one side and the differences of each file that doesn't match. The `json-diff` command
+//! line tool does the same with `json-diff dir <LHS> <RHS>`.
+//!
+//! ## YAML
+//!
+//! With the `yaml` feature enabled, `assert_yaml_include!`, `assert_yaml_eq!`, and
+//! `assert_yaml_matches!` compare YAML text, such as fixtures or Kubernetes manifests:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! assert-json-diff = { version = "2.0.2", features = ["yaml"] }
+//! ```
+//!
+//! The YAML is converted to JSON with `yaml_to_json` first, so differences are reported with the
+//! usual paths. YAML that has no JSON equivalent, such as mapping keys that aren't strings, anchors
+//! and aliases, or custom tags, is reported as a `YamlError` pointing at the offending line.
+//!
+//! ## Colored diffs
+//!
+//! Large values are easier to compare with the `color` feature enabled:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! assert-json-diff = { version = "2.0.2", features = ["color"] }
+//! ```
+//!
+//! Calling `Config::color_diff` then renders failures as a colored diff of the two values, either
+//! unified or side by side, with the changed parts of each line highlighted. Colors are turned
+//! off when the `NO_COLOR` environment variable is set or stdout isn't a terminal.
+//!
+//! ## Command line
+//!
+//! The `cli` feature adds a `json-diff` binary which compares two JSON files, or one file and stdin
+//! when given `-`, and exits with 1 if they differ:
+//!
+//! ```text
+//! cargo install assert-json-diff --features cli
+//! json-diff --inclusive --ignore-path '..etag' --format json actual.json expected.json
+//! ```
+//!
+//! The flags map onto `Config`, and `--format` prints the differences as the usual message, a JSON
+//! array, or, for strict comparisons, a JSON Patch.
 
 #![deny(
     missing_docs,
@@ -152,11 +270,50 @@
     unknown_lints
 )]
 
-use diff::diff;
+use context::render_context;
+use diff::{diff, diff_limited, KeyRef, OmittedGroup};
+use matcher::SharedMatcher;
+use path_pattern::PathPattern;
+use placeholder::{Placeholder, PlaceholderSyntax};
+use redact::{redact, Redaction};
 use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
 
+mod context;
 mod core_ext;
 mod diff;
+mod dir;
+mod error;
+mod guided;
+mod matcher;
+mod merge_patch;
+mod patch;
+mod path_pattern;
+mod placeholder;
+mod redact;
+#[cfg(feature = "color")]
+mod render;
+mod snapshot;
+mod soft;
+mod source;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+pub use diff::{Difference, DifferenceKind, Key, Path};
+pub use dir::{diff_dirs, DirComparison, DirReport};
+#[cfg(feature = "yaml")]
+pub use error::YamlError;
+pub use error::{JsonDiffError, Mismatch, ParseError, PathPatternError, ToleranceError};
+pub use matcher::Matcher;
+pub use merge_patch::{apply_merge_patch, assert_json_merge_patch_no_panic, merge_patch};
+pub use patch::json_patch;
+#[cfg(feature = "color")]
+pub use render::{colors_enabled, DiffStyle};
+pub use snapshot::assert_json_snapshot_no_panic;
+pub use soft::JsonAssertions;
+#[cfg(feature = "yaml")]
+pub use yaml::yaml_to_json;
 
 /// Compare two JSON values for an inclusive match.
 ///
@@ -257,72 +414,557 @@ macro_rules! assert_json_matches {
     }};
 }
 
+/// Compare two pieces of JSON text for an inclusive match.
+///
+/// Like [`assert_json_include`](macro.assert_json_include.html) but takes JSON text rather than
+/// values that implement `Serialize`. See [`assert_json_text_matches_no_panic`].
+///
+/// ```
+/// use assert_json_diff::assert_json_text_include;
+///
+/// let body = r#"{ "id": 1, "name": "Bob" }"#;
+///
+/// assert_json_text_include!(actual: body, expected: r#"{ "name": "Bob" }"#);
+/// ```
+#[macro_export]
+macro_rules! assert_json_text_include {
+    (actual: $actual:expr, expected: $expected:expr $(,)?) => {{
+        $crate::assert_json_text_matches!(
+            $actual,
+            $expected,
+            $crate::Config::new($crate::CompareMode::Inclusive)
+        )
+    }};
+    (expected: $expected:expr, actual: $actual:expr $(,)?) => {{
+        $crate::assert_json_text_include!(actual: $actual, expected: $expected)
+    }};
+}
+
+/// Compare two pieces of JSON text for an exact match.
+///
+/// Like [`assert_json_eq`](macro.assert_json_eq.html) but takes JSON text rather than values
+/// that implement `Serialize`. See [`assert_json_text_matches_no_panic`].
+#[macro_export]
+macro_rules! assert_json_text_eq {
+    ($lhs:expr, $rhs:expr $(,)?) => {{
+        $crate::assert_json_text_matches!(
+            $lhs,
+            $rhs,
+            $crate::Config::new($crate::CompareMode::Strict)
+        )
+    }};
+}
+
+/// Compare two pieces of JSON text according to a configuration.
+///
+/// Like [`assert_json_matches`](macro.assert_json_matches.html) but takes JSON text rather than
+/// values that implement `Serialize`. See [`assert_json_text_matches_no_panic`].
+#[macro_export]
+macro_rules! assert_json_text_matches {
+    ($lhs:expr, $rhs:expr, $config:expr $(,)?) => {{
+        if let Err(error) = $crate::assert_json_text_matches_no_panic(&$lhs, &$rhs, $config) {
+            panic!("\n\n{}\n\n", error);
+        }
+    }};
+}
+
+/// Compare two pieces of YAML text for an inclusive match.
+///
+/// Like [`assert_json_include`](macro.assert_json_include.html) but takes YAML text, which is
+/// converted to JSON with [`yaml_to_json`]. See [`assert_yaml_matches_no_panic`].
+///
+/// ```
+/// use assert_json_diff::assert_yaml_include;
+///
+/// let manifest = "
+/// kind: Deployment
+/// spec:
+///   replicas: 3
+///   template:
+///     spec:
+///       containers:
+///         - name: web
+///           image: web:1.2
+/// ";
+///
+/// assert_yaml_include!(actual: manifest, expected: "spec: { replicas: 3 }");
+/// ```
+#[cfg(feature = "yaml")]
+#[macro_export]
+macro_rules! assert_yaml_include {
+    (actual: $actual:expr, expected: $expected:expr $(,)?) => {{
+        $crate::assert_yaml_matches!(
+            $actual,
+            $expected,
+            $crate::Config::new($crate::CompareMode::Inclusive)
+        )
+    }};
+    (expected: $expected:expr, actual: $actual:expr $(,)?) => {{
+        $crate::assert_yaml_include!(actual: $actual, expected: $expected)
+    }};
+}
+
+/// Compare two pieces of YAML text for an exact match.
+///
+/// Like [`assert_json_eq`](macro.assert_json_eq.html) but takes YAML text, which is converted to
+/// JSON with [`yaml_to_json`]. See [`assert_yaml_matches_no_panic`].
+#[cfg(feature = "yaml")]
+#[macro_export]
+macro_rules! assert_yaml_eq {
+    ($lhs:expr, $rhs:expr $(,)?) => {{
+        $crate::assert_yaml_matches!($lhs, $rhs, $crate::Config::new($crate::CompareMode::Strict))
+    }};
+}
+
+/// Compare two pieces of YAML text according to a configuration.
+///
+/// Like [`assert_json_matches`](macro.assert_json_matches.html) but takes YAML text, which is
+/// converted to JSON with [`yaml_to_json`]. See [`assert_yaml_matches_no_panic`].
+#[cfg(feature = "yaml")]
+#[macro_export]
+macro_rules! assert_yaml_matches {
+    ($lhs:expr, $rhs:expr, $config:expr $(,)?) => {{
+        if let Err(error) = $crate::assert_yaml_matches_no_panic(&$lhs, &$rhs, $config) {
+            panic!("\n\n{}\n\n", error);
+        }
+    }};
+}
+
+/// Compare a value to a JSON snapshot file.
+///
+/// The file is created with the value the first time, and rewritten when the
+/// `UPDATE_JSON_SNAPSHOTS` environment variable is set to `1`. Relative paths are relative to the
+/// root of the crate calling the macro. The comparison is exact unless a [`Config`] is given, in
+/// which case the value is "actual" and the snapshot is "expected".
+///
+/// ```
+/// # let dir = std::env::temp_dir().join(format!("assert-json-diff-doc-{}", std::process::id()));
+/// # let _ = std::fs::remove_dir_all(&dir);
+/// # let path = dir.join("user.json");
+/// use assert_json_diff::{assert_json_snapshot, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let user = json!({ "id": 1, "name": "Bob", "last_seen": "2021-01-23T12:00:00Z" });
+///
+/// // creates the snapshot
+/// assert_json_snapshot!(user, &path);
+///
+/// // compares against it
+/// assert_json_snapshot!(
+///     json!({ "id": 1, "name": "Bob", "last_seen": "2021-02-01T08:00:00Z" }),
+///     &path,
+///     Config::new(CompareMode::Strict).ignore_path(".last_seen"),
+/// );
+/// ```
+///
+/// On a mismatch it panics with the same message as
+/// [`assert_json_matches`](macro.assert_json_matches.html), followed by the path of the snapshot.
+/// See [`assert_json_snapshot_no_panic`] for details.
+#[macro_export]
+macro_rules! assert_json_snapshot {
+    ($value:expr, $path:expr $(,)?) => {{
+        $crate::assert_json_snapshot!(
+            $value,
+            $path,
+            $crate::Config::new($crate::CompareMode::Strict)
+        )
+    }};
+    ($value:expr, $path:expr, $config:expr $(,)?) => {{
+        let path = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path);
+        if let Err(error) = $crate::assert_json_snapshot_no_panic(&$value, path, $config) {
+            panic!("\n\n{}\n\n", error);
+        }
+    }};
+}
+
+/// Apply a JSON Merge Patch and compare the result to an expected value for an exact match.
+///
+/// This is useful for testing endpoints that accept [RFC 7396] merge patches, such as `PATCH`
+/// requests. If the patched value doesn't match you get the same output as with
+/// [`assert_json_eq`](macro.assert_json_eq.html), where `lhs` is the patched value.
+///
+/// ```
+/// use assert_json_diff::assert_json_merge_patch;
+/// use serde_json::json;
+///
+/// assert_json_merge_patch!(
+///     base: json!({ "name": "Bob", "email": "bob@example.com" }),
+///     patch: json!({ "name": "Alice", "email": null }),
+///     expected: json!({ "name": "Alice" }),
+/// );
+/// ```
+///
+/// [RFC 7396]: https://tools.ietf.org/html/rfc7396
+#[macro_export]
+macro_rules! assert_json_merge_patch {
+    (base: $base:expr, patch: $patch:expr, expected: $expected:expr $(,)?) => {{
+        if let Err(error) = $crate::assert_json_merge_patch_no_panic(&$base, &$patch, &$expected) {
+            panic!("\n\n{}\n\n", error);
+        }
+    }};
+}
+
 /// Compares two JSON values without panicking.
 ///
-/// Instead it returns a `Result` where the error is the message that would be passed to `panic!`.
-/// This is might be useful if you want to control how failures are reported and don't want to deal
-/// with panics.
+/// Instead it returns a `Result` where the error's `Display` implementation gives the message that
+/// would be passed to `panic!`. This is might be useful if you want to control how failures are
+/// reported and don't want to deal with panics. [`JsonDiffError`] implements
+/// [`std::error::Error`] so it can be propagated with `?`.
+///
+/// The differences are listed in the order described in [`diff_json`].
 pub fn assert_json_matches_no_panic<Lhs, Rhs>(
     lhs: &Lhs,
     rhs: &Rhs,
     config: Config,
-) -> Result<(), String>
+) -> Result<(), JsonDiffError>
 where
     Lhs: Serialize,
     Rhs: Serialize,
 {
-    let lhs = serde_json::to_value(lhs).unwrap_or_else(|err| {
-        panic!(
-            "Couldn't convert left hand side value to JSON. Serde error: {}",
-            err
-        )
-    });
-    let rhs = serde_json::to_value(rhs).unwrap_or_else(|err| {
-        panic!(
-            "Couldn't convert right hand side value to JSON. Serde error: {}",
-            err
-        )
-    });
+    let (lhs, rhs) = if config.is_guided_serialization() {
+        let rhs = serde_json::to_value(rhs).map_err(JsonDiffError::RhsSerialization)?;
+        let lhs = guided::to_value(lhs, &rhs, &config).map_err(JsonDiffError::LhsSerialization)?;
+        (lhs, rhs)
+    } else {
+        let lhs = serde_json::to_value(lhs).map_err(JsonDiffError::LhsSerialization)?;
+        let rhs = serde_json::to_value(rhs).map_err(JsonDiffError::RhsSerialization)?;
+        (lhs, rhs)
+    };
+
+    compare(&lhs, &rhs, &config, None)
+}
+
+/// Compares two pieces of JSON text without panicking.
+///
+/// Like [`assert_json_matches_no_panic`] but takes JSON text, such as the body of an HTTP
+/// response, as a `&str`, `&[u8]`, `String`, or anything else that implements `AsRef<[u8]>`.
+/// Invalid JSON gives a [`ParseError`] pointing at the line and column of the problem, and each
+/// difference in the failure message includes the line it is on in the left hand side text:
+///
+/// ```
+/// use assert_json_diff::{assert_json_text_matches_no_panic, CompareMode, Config};
+///
+/// let body = r#"{
+///     "id": 1,
+///     "name": "Bob"
+/// }"#;
+///
+/// let error = assert_json_text_matches_no_panic(
+///     body,
+///     r#"{ "name": "Alice" }"#,
+///     Config::new(CompareMode::Inclusive),
+/// )
+/// .unwrap_err();
+///
+/// assert_eq!(
+///     error.to_string(),
+///     r#"json atoms at path ".name" (line 3 of actual) are not equal:
+///     expected:
+///         "Alice"
+///     actual:
+///         "Bob""#
+/// );
+/// ```
+pub fn assert_json_text_matches_no_panic<Lhs, Rhs>(
+    lhs: &Lhs,
+    rhs: &Rhs,
+    config: Config,
+) -> Result<(), JsonDiffError>
+where
+    Lhs: AsRef<[u8]> + ?Sized,
+    Rhs: AsRef<[u8]> + ?Sized,
+{
+    compare_text(lhs.as_ref(), rhs.as_ref(), &config)
+}
+
+/// Parse and compare two pieces of JSON text.
+pub(crate) fn compare_text(
+    lhs_text: &[u8],
+    rhs_text: &[u8],
+    config: &Config,
+) -> Result<(), JsonDiffError> {
+    let lhs = serde_json::from_slice(lhs_text)
+        .map_err(|err| JsonDiffError::LhsParse(ParseError::new(err, lhs_text)))?;
+    let rhs = serde_json::from_slice(rhs_text)
+        .map_err(|err| JsonDiffError::RhsParse(ParseError::new(err, rhs_text)))?;
+
+    compare(&lhs, &rhs, config, Some(lhs_text))
+}
+
+/// Compares two pieces of YAML text without panicking.
+///
+/// Both sides are converted to JSON with [`yaml_to_json`] and then compared like
+/// [`assert_json_matches_no_panic`], so differences are reported with the same paths, such as
+/// `.spec.containers[0].image`. YAML that can't be converted gives a [`YamlError`]. Since JSON is
+/// valid YAML, either side can also be JSON text:
+///
+/// ```
+/// use assert_json_diff::{assert_yaml_matches_no_panic, CompareMode, Config};
+///
+/// let error = assert_yaml_matches_no_panic(
+///     r#"{ "spec": { "containers": [{ "name": "web", "image": "web:1.3" }] } }"#,
+///     "
+/// spec:
+///   containers:
+///     - image: web:1.2
+/// ",
+///     Config::new(CompareMode::Inclusive),
+/// )
+/// .unwrap_err();
+///
+/// assert_eq!(
+///     error.to_string(),
+///     r#"json atoms at path ".spec.containers[0].image" are not equal:
+///     expected:
+///         "web:1.2"
+///     actual:
+///         "web:1.3""#
+/// );
+/// ```
+#[cfg(feature = "yaml")]
+pub fn assert_yaml_matches_no_panic<Lhs, Rhs>(
+    lhs: &Lhs,
+    rhs: &Rhs,
+    config: Config,
+) -> Result<(), JsonDiffError>
+where
+    Lhs: AsRef<str> + ?Sized,
+    Rhs: AsRef<str> + ?Sized,
+{
+    let lhs = yaml_to_json(lhs.as_ref()).map_err(JsonDiffError::LhsYaml)?;
+    let rhs = yaml_to_json(rhs.as_ref()).map_err(JsonDiffError::RhsYaml)?;
+
+    compare(&lhs, &rhs, &config, None)
+}
+
+/// Compare two values, using `lhs_text` to find the line of each difference if given.
+pub(crate) fn compare(
+    lhs: &Value,
+    rhs: &Value,
+    config: &Config,
+    lhs_text: Option<&[u8]>,
+) -> Result<(), JsonDiffError> {
+    let (lhs, rhs) = (&*redact(lhs, config), &*redact(rhs, config));
+    let mut diffs = diff_limited(lhs, rhs, config);
 
-    let diffs = diff(&lhs, &rhs, config);
+    if let Some(text) = lhs_text {
+        for difference in &mut diffs.found {
+            let line = source::line_of(text, difference.path().keys());
+            difference.set_line(line);
+        }
+    }
 
-    if diffs.is_empty() {
+    if diffs.found.is_empty() && diffs.omitted.is_empty() {
         Ok(())
     } else {
-        let msg = diffs
-            .into_iter()
-            .map(|d| d.to_string())
+        let mut msg = diffs
+            .found
+            .iter()
+            .map(|d| render_difference(d, lhs, config))
             .collect::<Vec<_>>()
             .join("\n\n");
-        Err(msg)
+        if !diffs.omitted.is_empty() {
+            if !msg.is_empty() {
+                msg.push_str("\n\n");
+            }
+            msg.push_str(&summarize_omitted(&diffs.omitted));
+        }
+        let omitted = diffs.omitted_count();
+        Err(JsonDiffError::Mismatch(Mismatch::new(
+            diffs.found,
+            omitted,
+            msg,
+        )))
     }
 }
 
+fn summarize_omitted(omitted: &[OmittedGroup]) -> String {
+    omitted
+        .iter()
+        .map(|group| {
+            let noun = if group.count == 1 {
+                "difference"
+            } else {
+                "differences"
+            };
+            if group.is_root() {
+                format!("... and {} more {}", separate_thousands(group.count), noun)
+            } else {
+                format!(
+                    "... and {} more {} under \"{}\"",
+                    separate_thousands(group.count),
+                    noun,
+                    group
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Format a number with commas between the thousands, such as `2,317`.
+fn separate_thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (idx, digit) in digits.chars().enumerate() {
+        // a comma goes before every digit that has a multiple of three digits after it
+        if idx > 0 && idx % 3 == digits.len() % 3 {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+fn render_difference(difference: &Difference, lhs: &Value, config: &Config) -> String {
+    #[cfg(feature = "color")]
+    let mut msg = match config.diff_style {
+        Some(style) if colors_enabled() => difference.to_colored_string(style),
+        _ => difference.to_string(),
+    };
+    #[cfg(not(feature = "color"))]
+    let mut msg = difference.to_string();
+
+    let context = render_context(
+        difference,
+        lhs,
+        config.context_levels,
+        config.max_context_siblings,
+    );
+    if let Some(context) = context {
+        msg.push('\n');
+        msg.push_str(&context);
+    }
+    msg
+}
+
+/// Compares two JSON values and returns the differences.
+///
+/// Unlike [`assert_json_matches_no_panic`] this gives you structured [`Difference`]s rather than a
+/// single message, so they can be inspected, filtered, and rendered programmatically. An empty
+/// `Vec` means the values match.
+///
+/// When using [`CompareMode::Inclusive`] `lhs` is "actual" and `rhs` is "expected". At most
+/// [`Config::max_differences`] differences are returned.
+///
+/// # Order of differences
+///
+/// The differences are always returned in the same order, so error messages can be compared
+/// across runs. Values are visited depth first. Array elements are visited by index. Object fields
+/// are visited in the order of the left hand side, followed by the fields that are only on the
+/// right hand side. With [`CompareMode::Inclusive`] only the fields of the right hand side are
+/// visited, in its order.
+///
+/// Note that `serde_json` sorts object fields by key unless its `preserve_order` feature is
+/// enabled.
+pub fn diff_json(lhs: &Value, rhs: &Value, config: &Config) -> Vec<Difference> {
+    diff(&redact(lhs, config), &redact(rhs, config), config)
+}
+
+/// Checks whether two JSON values match according to a configuration.
+///
+/// This uses the same rules as [`assert_json_matches_no_panic`] and [`diff_json`] but stops at the
+/// first difference and doesn't build any messages, which makes it a good fit for property tests
+/// and hot loops where you only need a yes or no.
+///
+/// ```
+/// use assert_json_diff::{json_matches, CompareMode, Config};
+/// use serde_json::json;
+///
+/// let config = Config::new(CompareMode::Inclusive);
+///
+/// assert!(json_matches(&json!({ "a": 1, "b": 2 }), &json!({ "a": 1 }), &config));
+/// assert!(!json_matches(&json!({ "a": 1 }), &json!({ "a": 2 }), &config));
+/// ```
+pub fn json_matches(lhs: &Value, rhs: &Value, config: &Config) -> bool {
+    diff::matches(&redact(lhs, config), &redact(rhs, config), config)
+}
+
 /// Configuration for how JSON values should be compared.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 #[allow(missing_copy_implementations)]
 pub struct Config {
     pub(crate) compare_mode: CompareMode,
     pub(crate) numeric_mode: NumericMode,
+    pub(crate) array_sorting_mode: ArraySortingMode,
+    pub(crate) ignored_paths: Vec<PathPattern>,
+    pub(crate) placeholder_syntax: Option<PlaceholderSyntax>,
+    pub(crate) path_matchers: Vec<(PathPattern, SharedMatcher)>,
+    pub(crate) placeholder_matchers: HashMap<String, SharedMatcher>,
+    pub(crate) max_differences: Option<usize>,
+    pub(crate) context_levels: usize,
+    pub(crate) max_context_siblings: usize,
+    pub(crate) guided_serialization: bool,
+    pub(crate) redactions: Vec<Redaction>,
+    #[cfg(feature = "color")]
+    pub(crate) diff_style: Option<DiffStyle>,
 }
 
 impl Config {
     /// Create a new [`Config`] using the given [`CompareMode`].
     ///
-    /// The default `numeric_mode` is be [`NumericMode::Strict`].
+    /// The default `numeric_mode` is be [`NumericMode::Strict`] and the default
+    /// `array_sorting_mode` is [`ArraySortingMode::Strict`].
     pub fn new(compare_mode: CompareMode) -> Self {
         Self {
             compare_mode,
             numeric_mode: NumericMode::Strict,
+            array_sorting_mode: ArraySortingMode::Strict,
+            ignored_paths: Vec::new(),
+            placeholder_syntax: None,
+            path_matchers: Vec::new(),
+            placeholder_matchers: HashMap::new(),
+            max_differences: None,
+            context_levels: 0,
+            max_context_siblings: 3,
+            guided_serialization: false,
+            redactions: Vec::new(),
+            #[cfg(feature = "color")]
+            diff_style: None,
         }
     }
 
     /// Change the config's numeric mode.
     ///
     /// The default `numeric_mode` is be [`NumericMode::Strict`].
-    pub fn numeric_mode(mut self, numeric_mode: NumericMode) -> Self {
-        self.numeric_mode = numeric_mode;
-        self
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tolerance of [`NumericMode::AbsoluteTolerance`] or
+    /// [`NumericMode::RelativeTolerance`] is negative, infinite, or NaN.
+    pub fn numeric_mode(self, numeric_mode: NumericMode) -> Self {
+        self.try_numeric_mode(numeric_mode)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Like [`Config::numeric_mode`] but returns an error rather than panicking if the tolerance
+    /// is invalid, such as when the tolerance comes from user input.
+    ///
+    /// ```
+    /// use assert_json_diff::{CompareMode, Config, NumericMode};
+    ///
+    /// let error = Config::new(CompareMode::Strict)
+    ///     .try_numeric_mode(NumericMode::AbsoluteTolerance(-0.5))
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Invalid tolerance `-0.5`: tolerances must be finite and not negative"
+    /// );
+    /// ```
+    pub fn try_numeric_mode(mut self, numeric_mode: NumericMode) -> Result<Self, ToleranceError> {
+        match numeric_mode {
+            NumericMode::AbsoluteTolerance(tolerance)
+            | NumericMode::RelativeTolerance(tolerance)
+                if !tolerance.is_finite() || tolerance < 0.0 =>
+            {
+                Err(ToleranceError::new(tolerance))
+            }
+            _ => {
+                self.numeric_mode = numeric_mode;
+                Ok(self)
+            }
+        }
     }
 
     /// Change the config's compare mode.
@@ -330,6 +972,380 @@ impl Config {
         self.compare_mode = compare_mode;
         self
     }
+
+    /// Change the config's array sorting mode.
+    ///
+    /// The default `array_sorting_mode` is [`ArraySortingMode::Strict`].
+    pub fn array_sorting_mode(mut self, array_sorting_mode: ArraySortingMode) -> Self {
+        self.array_sorting_mode = array_sorting_mode;
+        self
+    }
+
+    /// Ignore values at paths matching the given pattern.
+    ///
+    /// Ignored values are skipped entirely, on both sides, regardless of the compare mode. This is
+    /// useful for volatile fields such as ids or timestamps.
+    ///
+    /// The pattern uses the same syntax as paths in error messages, with some additions:
+    ///
+    /// - `.name` matches the field `name`. Use `["name"]` for names containing `.`, `[`, or `]`.
+    /// - `.*` matches any field.
+    /// - `[3]` matches index 3 of an array.
+    /// - `[*]` matches any index.
+    /// - `..` matches any number of fields and indexes, including none. For example `..etag`
+    ///   matches a field called `etag` anywhere in the document.
+    ///
+    /// ```
+    /// use assert_json_diff::{assert_json_matches, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict)
+    ///     .ignore_path(".created_at")
+    ///     .ignore_path(".users[*].id");
+    ///
+    /// assert_json_matches!(
+    ///     json!({
+    ///         "created_at": "2021-01-23T12:00:00Z",
+    ///         "users": [{ "id": 1, "name": "Bob" }],
+    ///     }),
+    ///     json!({
+    ///         "users": [{ "id": 2, "name": "Bob" }],
+    ///     }),
+    ///     config,
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is invalid.
+    pub fn ignore_path(self, pattern: &str) -> Self {
+        self.try_ignore_path(pattern)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Like [`Config::ignore_path`] but returns an error rather than panicking if the pattern is
+    /// invalid, such as when the pattern comes from user input.
+    ///
+    /// ```
+    /// use assert_json_diff::{CompareMode, Config};
+    ///
+    /// let error = Config::new(CompareMode::Strict)
+    ///     .try_ignore_path("users[*]")
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Invalid path pattern `users[*]`: expected `.` or `[` but found `u`, paths must start with one of them"
+    /// );
+    /// ```
+    pub fn try_ignore_path(mut self, pattern: &str) -> Result<Self, PathPatternError> {
+        let pattern =
+            PathPattern::parse(pattern).map_err(|reason| PathPatternError::new(pattern, reason))?;
+        self.ignored_paths.push(pattern);
+        Ok(self)
+    }
+
+    /// Replace values at paths matching the given pattern with `"[redacted:<label>]"`.
+    ///
+    /// Redaction happens on both sides before comparing, so tokens, timestamps, and other values
+    /// that change between runs or shouldn't be seen never show up in failure messages,
+    /// [`Difference`]s, or snapshots written by
+    /// [`assert_json_snapshot`](macro.assert_json_snapshot.html). Unlike [`Config::ignore_path`]
+    /// the redacted value must still be present. The pattern syntax is described in
+    /// [`Config::ignore_path`].
+    ///
+    /// ```
+    /// use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Inclusive).redact_path("..token", "token");
+    ///
+    /// let error = assert_json_matches_no_panic(
+    ///     &json!({ "user": "Bob", "session": { "token": "s3cr3t" } }),
+    ///     &json!({ "user": "Alice", "session": { "token": "[redacted:token]" } }),
+    ///     config,
+    /// )
+    /// .unwrap_err();
+    ///
+    /// assert!(!error.to_string().contains("s3cr3t"));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is invalid.
+    pub fn redact_path(mut self, pattern: &str, label: &str) -> Self {
+        let pattern = PathPattern::parse(pattern)
+            .unwrap_or_else(|err| panic!("Invalid path pattern `{}`: {}", pattern, err));
+        self.redactions.push(Redaction::path(pattern, label));
+        self
+    }
+
+    /// Replace values accepted by `predicate` with `"[redacted:<label>]"`, wherever they are.
+    ///
+    /// Works like [`Config::redact_path`]. Objects and arrays are checked before their contents
+    /// and are replaced as a whole if accepted.
+    ///
+    /// ```
+    /// use assert_json_diff::{assert_json_matches, CompareMode, Config};
+    /// use serde_json::{json, Value};
+    ///
+    /// let config = Config::new(CompareMode::Strict).redact_values("jwt", |value: &Value| {
+    ///     value.as_str().is_some_and(|s| s.starts_with("eyJ"))
+    /// });
+    ///
+    /// assert_json_matches!(
+    ///     json!({ "access": "eyJhbGciOi.first", "refresh": "eyJhbGciOi.second" }),
+    ///     json!({ "access": "[redacted:jwt]", "refresh": "[redacted:jwt]" }),
+    ///     config,
+    /// );
+    /// ```
+    pub fn redact_values<F>(mut self, label: &str, predicate: F) -> Self
+    where
+        F: Fn(&Value) -> bool + Send + Sync + 'static,
+    {
+        self.redactions.push(Redaction::value(label, predicate));
+        self
+    }
+
+    /// Treat strings like `"${any_uuid}"` on the right hand side as matchers rather than literal
+    /// values.
+    ///
+    /// The right hand side is "expected" when using [`CompareMode::Inclusive`]. The available
+    /// placeholders are:
+    ///
+    /// - `${any}` matches any value, including `null`.
+    /// - `${any_string}`, `${any_number}`, and `${any_bool}` match any value of that type.
+    /// - `${any_uuid}` matches a string containing a UUID, such as
+    ///   `"67e55044-10b1-426f-9247-bb680e5fe0c8"`.
+    /// - `${iso8601}` matches a string containing an ISO 8601 date, such as `"2021-01-23"`, or an
+    ///   RFC 3339 date-time, such as `"2021-01-23T12:00:00Z"`.
+    /// - `${regex:<pattern>}` matches a string matching the regular expression.
+    /// - `${non_empty}` matches a non-empty string, array, or object.
+    ///
+    /// ```
+    /// use assert_json_diff::{assert_json_matches, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// assert_json_matches!(
+    ///     json!({
+    ///         "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+    ///         "created": "2021-01-23T12:00:00Z",
+    ///         "count": 3,
+    ///     }),
+    ///     json!({
+    ///         "id": "${any_uuid}",
+    ///         "created": "${iso8601}",
+    ///         "count": "${any_number}",
+    ///     }),
+    ///     Config::new(CompareMode::Inclusive).enable_placeholders(),
+    /// );
+    /// ```
+    ///
+    /// Use [`Config::placeholder_syntax`] if `${` and `}` clash with your data.
+    pub fn enable_placeholders(mut self) -> Self {
+        self.placeholder_syntax = Some(PlaceholderSyntax::default());
+        self
+    }
+
+    /// Enable placeholders, like [`Config::enable_placeholders`], but with a custom prefix and
+    /// suffix.
+    ///
+    /// For example with `placeholder_syntax("{{", "}}")` the placeholders are written like
+    /// `"{{any_uuid}}"`.
+    pub fn placeholder_syntax(mut self, prefix: &str, suffix: &str) -> Self {
+        self.placeholder_syntax = Some(PlaceholderSyntax::new(prefix, suffix));
+        self
+    }
+
+    /// Check values at paths matching the given pattern with a custom [`Matcher`].
+    ///
+    /// The matcher replaces the normal comparison of the values at those paths, so the value on
+    /// the right hand side can be anything, for example `null`. The path must still be present on
+    /// both sides for the matcher to be used. See [`Config::ignore_path`] for the pattern syntax.
+    ///
+    /// See [`Matcher`] for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is invalid.
+    pub fn matcher_at<M>(mut self, pattern: &str, matcher: M) -> Self
+    where
+        M: Matcher + 'static,
+    {
+        let pattern = PathPattern::parse(pattern)
+            .unwrap_or_else(|err| panic!("Invalid path pattern `{}`: {}", pattern, err));
+        self.path_matchers
+            .push((pattern, SharedMatcher::new(matcher)));
+        self
+    }
+
+    /// Register a custom [`Matcher`] as a placeholder.
+    ///
+    /// With the name `"money"` the matcher is used for the string `"${money}"` on the right hand
+    /// side. This enables placeholders, using the default syntax unless
+    /// [`Config::placeholder_syntax`] has been called. Custom placeholders take precedence over the
+    /// built-in ones described in [`Config::enable_placeholders`].
+    ///
+    /// See [`Matcher`] for an example.
+    pub fn placeholder_matcher<M>(mut self, name: &str, matcher: M) -> Self
+    where
+        M: Matcher + 'static,
+    {
+        self.placeholder_syntax.get_or_insert_with(Default::default);
+        self.placeholder_matchers
+            .insert(name.to_string(), SharedMatcher::new(matcher));
+        self
+    }
+
+    /// Show the surrounding JSON of each difference in failure messages, `levels` ancestors up.
+    ///
+    /// The ancestors are taken from the left hand side, which is "actual" when using
+    /// [`CompareMode::Inclusive`], and the differing value is marked inline. With one level you
+    /// get the object or array that contains the differing value. The default is zero levels,
+    /// which shows no context.
+    ///
+    /// ```should_panic
+    /// use assert_json_diff::{assert_json_matches, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// assert_json_matches!(
+    ///     json!({ "users": [{ "id": 1, "country": { "name": "Denmark" } }] }),
+    ///     json!({ "users": [{ "country": { "name": "Sweden" } }] }),
+    ///     Config::new(CompareMode::Inclusive).context(2),
+    /// );
+    /// ```
+    ///
+    /// This will panic with the error message:
+    ///
+    /// ```text
+    /// json atoms at path ".users[0].country.name" are not equal:
+    ///     expected:
+    ///         "Sweden"
+    ///     actual:
+    ///         "Denmark"
+    ///     in actual at path ".users[0]":
+    ///         {
+    ///           "country": {
+    ///             "name": "Denmark" <- expected "Sweden"
+    ///           },
+    ///           "id": 1
+    ///         }
+    /// ```
+    pub fn context(mut self, levels: usize) -> Self {
+        self.context_levels = levels;
+        self
+    }
+
+    /// Change how many unchanged siblings are shown at each level of the context.
+    ///
+    /// The siblings closest to the differing value are shown and the rest are replaced with
+    /// `...`. The default is 3. See [`Config::context`].
+    pub fn max_context_siblings(mut self, limit: usize) -> Self {
+        self.max_context_siblings = limit;
+        self
+    }
+
+    /// Stop collecting differences after the first `max`.
+    ///
+    /// Differences beyond the limit are only counted and summarized at the end of failure
+    /// messages. They are grouped by the top level field they are under, or together if the root
+    /// is an array, and each group is shown with the longest path prefix its differences share.
+    /// Array indices that differ between them are shown as `[*]`:
+    ///
+    /// ```text
+    /// ... and 2,317 more differences under ".items"
+    /// ... and 12 more differences under ".meta.users[*].name"
+    /// ```
+    ///
+    /// The values of omitted differences aren't cloned or rendered, which makes comparing large
+    /// values that differ a lot faster. [`diff_json`] returns at most `max` differences.
+    pub fn max_differences(mut self, max: usize) -> Self {
+        self.max_differences = Some(max);
+        self
+    }
+
+    /// Only serialize the parts of "actual" that "expected" mentions.
+    ///
+    /// Normally both values are fully converted to JSON before being compared. With this enabled
+    /// "expected" is converted first and then guides the conversion of "actual", skipping object
+    /// fields that "expected" doesn't have. Array elements beyond the length of the expected array
+    /// are skipped as well, unless using [`ArraySortingMode::Ignore`] or
+    /// [`ArraySortingMode::Subsequence`]. That saves memory and time when checking a small part of
+    /// a large value.
+    ///
+    /// ```
+    /// use assert_json_diff::{assert_json_matches, CompareMode, Config};
+    /// use serde::Serialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Report {
+    ///     id: u32,
+    ///     rows: Vec<Vec<f64>>,
+    /// }
+    ///
+    /// let report = Report {
+    ///     id: 1,
+    ///     rows: vec![vec![0.0; 1_000]; 1_000],
+    /// };
+    ///
+    /// assert_json_matches!(
+    ///     report,
+    ///     json!({ "id": 1 }),
+    ///     Config::new(CompareMode::Inclusive).guided_serialization(),
+    /// );
+    /// ```
+    ///
+    /// Skipped values are never serialized, so they can't cause serialization errors. It only has
+    /// an effect with [`CompareMode::Inclusive`], since every value is compared when using
+    /// [`CompareMode::Strict`], and it is ignored when [`Config::context`] is used since the
+    /// context shows values that "expected" doesn't mention.
+    pub fn guided_serialization(mut self) -> Self {
+        self.guided_serialization = true;
+        self
+    }
+
+    /// Render failures with a colored diff of the values, laid out according to `style`.
+    ///
+    /// Colors are only used if [`colors_enabled`] returns `true`, so the output stays plain when
+    /// `NO_COLOR` is set or stdout isn't a terminal. See [`Difference::to_colored_string`].
+    ///
+    /// Requires the `color` feature.
+    #[cfg(feature = "color")]
+    pub fn color_diff(mut self, style: DiffStyle) -> Self {
+        self.diff_style = Some(style);
+        self
+    }
+
+    pub(crate) fn path_matcher(
+        &self,
+        path: &[KeyRef<'_>],
+    ) -> Option<(&PathPattern, &SharedMatcher)> {
+        self.path_matchers
+            .iter()
+            .find(|(pattern, _)| pattern.matches(path))
+            .map(|(pattern, matcher)| (pattern, matcher))
+    }
+
+    pub(crate) fn placeholder_matcher_named(&self, name: &str) -> Option<&SharedMatcher> {
+        self.placeholder_matchers.get(name)
+    }
+
+    pub(crate) fn placeholder<'a>(&'a self, value: &'a Value) -> Option<Placeholder<'a>> {
+        self.placeholder_syntax.as_ref()?.parse(value)
+    }
+
+    pub(crate) fn is_guided_serialization(&self) -> bool {
+        self.guided_serialization
+            && self.compare_mode == CompareMode::Inclusive
+            && self.context_levels == 0
+    }
+
+    pub(crate) fn is_ignored(&self, path: &[KeyRef<'_>]) -> bool {
+        self.ignored_paths
+            .iter()
+            .any(|pattern| pattern.matches(path))
+    }
 }
 
 /// Mode for how JSON values should be compared.
@@ -347,12 +1363,95 @@ pub enum CompareMode {
 }
 
 /// How should numbers be compared.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+///
+/// The tolerance modes are useful for computed values, such as averages or ratios, where exact
+/// float equality is meaningless:
+///
+/// ```
+/// use assert_json_diff::{assert_json_matches, CompareMode, Config, NumericMode};
+/// use serde_json::json;
+///
+/// let config = Config::new(CompareMode::Strict).numeric_mode(NumericMode::AbsoluteTolerance(0.01));
+///
+/// assert_json_matches!(
+///     json!({ "average": 0.1 + 0.2 }),
+///     json!({ "average": 0.3 }),
+///     config,
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum NumericMode {
     /// Different numeric types aren't considered equal.
     Strict,
     /// All numeric types are converted to float before comparison.
     AssumeFloat,
+    /// All numeric types are converted to float and are considered equal if the absolute
+    /// difference between them is at most the given tolerance.
+    ///
+    /// The tolerance must be finite and not negative, see [`Config::numeric_mode`].
+    AbsoluteTolerance(f64),
+    /// All numeric types are converted to float and are considered equal if the difference
+    /// between them, relative to the largest of the two, is at most the given tolerance.
+    ///
+    /// For example a tolerance of `0.01` allows the numbers to differ by 1%. Like
+    /// [`NumericMode::AbsoluteTolerance`] the tolerance must be finite and not negative.
+    RelativeTolerance(f64),
+    /// All numeric types are converted to float and are considered equal if there are at most the
+    /// given number of representable floats between them, that is their distance in "units in the
+    /// last place".
+    Ulps(u64),
+}
+
+/// How should the order of array elements be treated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArraySortingMode {
+    /// Arrays are compared index by index, so the elements must be in the same order.
+    Strict,
+    /// Arrays are compared as multisets, so the order of the elements doesn't matter.
+    ///
+    /// Each element on the right hand side is paired with an equal element on the left hand side.
+    /// When using [`CompareMode::Inclusive`] the left hand side is allowed to contain additional
+    /// elements, with [`CompareMode::Strict`] every element must have a partner.
+    ///
+    /// Elements that don't have an equal partner are paired with the closest remaining element,
+    /// and the differences between them are reported at the index of the element on the left hand
+    /// side. Elements on the right hand side that couldn't be paired at all are reported as
+    /// missing at their own index.
+    ///
+    /// ```
+    /// use assert_json_diff::{assert_json_matches, ArraySortingMode, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::new(CompareMode::Strict).array_sorting_mode(ArraySortingMode::Ignore);
+    ///
+    /// assert_json_matches!(
+    ///     json!({ "tags": ["b", "c", "a"] }),
+    ///     json!({ "tags": ["a", "b", "c"] }),
+    ///     config,
+    /// );
+    /// ```
+    Ignore,
+    /// Elements on the right hand side must appear in the same relative order on the left hand
+    /// side, but there may be other elements in between.
+    ///
+    /// This is useful with [`CompareMode::Inclusive`] to check that an array contains some
+    /// elements in a particular order. With [`CompareMode::Strict`] both arrays must contain the
+    /// same elements so this is the same as [`ArraySortingMode::Strict`].
+    ///
+    /// ```
+    /// use assert_json_diff::{assert_json_matches, ArraySortingMode, CompareMode, Config};
+    /// use serde_json::json;
+    ///
+    /// let config =
+    ///     Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Subsequence);
+    ///
+    /// assert_json_matches!(
+    ///     json!({ "events": ["created", "paid", "refunded", "closed"] }),
+    ///     json!({ "events": ["created", "closed"] }),
+    ///     config,
+    /// );
+    /// ```
+    Subsequence,
 }
 
 #[cfg(test)]
@@ -508,7 +1607,9 @@ mod tests {
         let result = test_partial_match(json!({}), json!({ "a": true }));
         assert_output_eq(
             result,
-            Err(r#"json atom at path ".a" is missing from actual"#),
+            Err(r#"json atom at path ".a" is missing from actual:
+    expected:
+        true"#),
         );
 
         let result = test_partial_match(json!({ "a": { "b": true } }), json!({ "a": true }));
@@ -565,7 +1666,9 @@ mod tests {
         let result = test_partial_match(json!({ "a": [1, 2]}), json!({ "a": [1, 2, 3]}));
         assert_output_eq(
             result,
-            Err(r#"json atom at path ".a[2]" is missing from actual"#),
+            Err(r#"json atom at path ".a[2]" is missing from actual:
+    expected:
+        3"#),
         );
     }
 
@@ -606,17 +1709,334 @@ mod tests {
         let result = test_exact_match(json!({ "a": { "b": 1 } }), json!({ "a": {} }));
         assert_output_eq(
             result,
-            Err(r#"json atom at path ".a.b" is missing from rhs"#),
+            Err(r#"json atom at path ".a.b" is missing from rhs:
+    lhs:
+        1"#),
         );
 
         let result = test_exact_match(json!({ "a": {} }), json!({ "a": { "b": 1 } }));
         assert_output_eq(
             result,
-            Err(r#"json atom at path ".a.b" is missing from lhs"#),
+            Err(r#"json atom at path ".a.b" is missing from lhs:
+    rhs:
+        1"#),
+        );
+    }
+
+    #[test]
+    fn numeric_tolerance_output_message() {
+        let result = assert_json_matches_no_panic(
+            &json!({ "a": 1.0 }),
+            &json!({ "a": 1.5 }),
+            Config::new(CompareMode::Inclusive).numeric_mode(NumericMode::AbsoluteTolerance(0.1)),
+        );
+        assert_output_eq(
+            result,
+            Err(
+                r#"json numbers at path ".a" differ by 0.5, which is more than the absolute tolerance of 0.1:
+    expected:
+        1.5
+    actual:
+        1.0"#,
+            ),
+        );
+
+        let result = assert_json_matches_no_panic(
+            &json!(2),
+            &json!(2.5),
+            Config::new(CompareMode::Strict).numeric_mode(NumericMode::RelativeTolerance(0.1)),
+        );
+        assert_output_eq(
+            result,
+            Err(
+                r#"json numbers at path "(root)" differ by a relative 0.2, which is more than the relative tolerance of 0.1:
+    lhs:
+        2
+    rhs:
+        2.5"#,
+            ),
+        );
+
+        let result = assert_json_matches_no_panic(
+            &json!([1.0]),
+            &json!([1.0000000000000007]),
+            Config::new(CompareMode::Strict).numeric_mode(NumericMode::Ulps(1)),
+        );
+        assert_output_eq(
+            result,
+            Err(
+                r#"json numbers at path "[0]" differ by 3 ULPs, which is more than the tolerance of 1 ULPs:
+    lhs:
+        1.0
+    rhs:
+        1.0000000000000007"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn placeholder_output_message() {
+        let result = assert_json_matches_no_panic(
+            &json!({ "id": "abc", "count": 1 }),
+            &json!({ "id": "${any_uuid}", "count": "${any_number}" }),
+            Config::new(CompareMode::Inclusive).enable_placeholders(),
+        );
+        assert_output_eq(
+            result,
+            Err(
+                r#"json atom at path ".id" was rejected by matcher "${any_uuid}": expected a UUID
+    actual:
+        "abc""#,
+            ),
+        );
+
+        let result = assert_json_matches_no_panic(
+            &json!([""]),
+            &json!(["{{non_empty}}"]),
+            Config::new(CompareMode::Strict).placeholder_syntax("{{", "}}"),
+        );
+        assert_output_eq(
+            result,
+            Err(
+                r#"json atom at path "[0]" was rejected by matcher "{{non_empty}}": expected a non-empty string, array, or object
+    lhs:
+        """#,
+            ),
+        );
+    }
+
+    #[test]
+    fn custom_matcher_output_message() {
+        let result = assert_json_matches_no_panic(
+            &json!({ "country": "XX" }),
+            &json!({ "country": "${country}" }),
+            Config::new(CompareMode::Inclusive).placeholder_matcher("country", |actual: &Value| {
+                Err(format!("{} is not a known country code", actual))
+            }),
+        );
+        assert_output_eq(
+            result,
+            Err(
+                r#"json atom at path ".country" was rejected by matcher "${country}": "XX" is not a known country code
+    actual:
+        "XX""#,
+            ),
+        );
+    }
+
+    #[test]
+    fn multiple_differences_output_message() {
+        let result = test_exact_match(
+            json!({ "b": [1, 2], "a": 1, "c": true }),
+            json!({ "d": null, "b": [3], "a": 2 }),
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json atoms at path ".a" are not equal:
+    lhs:
+        1
+    rhs:
+        2
+
+json atoms at path ".b[0]" are not equal:
+    lhs:
+        1
+    rhs:
+        3
+
+json atom at path ".b[1]" is missing from rhs:
+    lhs:
+        2
+
+json atom at path ".c" is missing from rhs:
+    lhs:
+        true
+
+json atom at path ".d" is missing from lhs:
+    rhs:
+        null"#),
         );
     }
 
-    fn assert_output_eq(actual: Result<(), String>, expected: Result<(), &str>) {
+    #[test]
+    fn context_output_message() {
+        let actual = json!({
+            "data": {
+                "users": [
+                    { "id": 1, "country": { "name": "Denmark" } },
+                    { "id": 2, "country": { "name": "Denmark" }, "tags": ["a", "b"] },
+                ]
+            }
+        });
+        let expected = json!({
+            "data": {
+                "users": [
+                    {},
+                    { "country": { "name": "Sweden", "code": "SE" } },
+                ]
+            }
+        });
+
+        let result = assert_json_matches_no_panic(
+            &actual,
+            &expected,
+            Config::new(CompareMode::Inclusive).context(2),
+        );
+        assert_output_eq(
+            result,
+            Err(
+                r#"json atom at path ".data.users[1].country.code" is missing from actual:
+    expected:
+        "SE"
+    in actual at path ".data.users[1]":
+        {
+          "country": {
+            "name": "Denmark",
+            "code": (missing) <- expected "SE"
+          },
+          "id": 2,
+          "tags": ["a","b"]
+        }
+
+json atoms at path ".data.users[1].country.name" are not equal:
+    expected:
+        "Sweden"
+    actual:
+        "Denmark"
+    in actual at path ".data.users[1]":
+        {
+          "country": {
+            "name": "Denmark" <- expected "Sweden"
+          },
+          "id": 2,
+          "tags": ["a","b"]
+        }"#,
+            ),
+        );
+
+        let result = assert_json_matches_no_panic(
+            &json!([1, 2, 3, 4, 5, 6, { "a": [7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18] }]),
+            &json!([1, 2, 3, 4, 0, 6, { "a": [7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18] }]),
+            Config::new(CompareMode::Strict)
+                .context(5)
+                .max_context_siblings(2),
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json atoms at path "[4]" are not equal:
+    lhs:
+        5
+    rhs:
+        0
+    in lhs at path "(root)":
+        [
+          ...
+          4,
+          5, <- rhs is 0
+          6,
+          ...
+        ]"#),
+        );
+
+        let result = assert_json_matches_no_panic(
+            &json!({ "a": 1 }),
+            &json!({ "a": 2 }),
+            Config::new(CompareMode::Strict),
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json atoms at path ".a" are not equal:
+    lhs:
+        1
+    rhs:
+        2"#),
+        );
+    }
+
+    #[test]
+    fn max_differences_output_message() {
+        let items = (0..2000).collect::<Vec<_>>();
+        let other_items = (1..2001).collect::<Vec<_>>();
+
+        let result = assert_json_matches_no_panic(
+            &json!({ "a": 1, "items": items, "meta": { "x": 1, "y": 1 } }),
+            &json!({ "a": 2, "items": other_items, "meta": { "x": 2, "y": 2 } }),
+            Config::new(CompareMode::Strict).max_differences(2),
+        );
+        assert_output_eq(
+            result,
+            Err(r#"json atoms at path ".a" are not equal:
+    lhs:
+        1
+    rhs:
+        2
+
+json atoms at path ".items[0]" are not equal:
+    lhs:
+        0
+    rhs:
+        1
+
+... and 1,999 more differences under ".items"
+... and 2 more differences under ".meta""#),
+        );
+
+        let error = assert_json_matches_no_panic(
+            &json!([1, 2]),
+            &json!([3, 4]),
+            Config::new(CompareMode::Strict).max_differences(0),
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "... and 2 more differences");
+        match error {
+            JsonDiffError::Mismatch(mismatch) => {
+                assert!(mismatch.differences().is_empty());
+                assert_eq!(mismatch.omitted(), 2);
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+
+        let result = assert_json_matches_no_panic(
+            &json!({ "a": 1, "b": 1 }),
+            &json!({ "a": 2, "b": 2 }),
+            Config::new(CompareMode::Strict).max_differences(1),
+        );
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .ends_with(r#"... and 1 more difference under ".b""#));
+
+        let result = assert_json_matches_no_panic(
+            &json!({ "data": { "users": [{ "id": 1, "name": "a" }, { "id": 2, "name": "b" }] } }),
+            &json!({ "data": { "users": [{ "id": 1, "name": "c" }, { "id": 2, "name": "d" }] } }),
+            Config::new(CompareMode::Strict).max_differences(0),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            r#"... and 2 more differences under ".data.users[*].name""#
+        );
+
+        let result = assert_json_matches_no_panic(
+            &json!([{ "a": 1, "b": { "c": 1, "d": 1 } }, { "a": 1, "b": { "c": 1 } }]),
+            &json!([{ "a": 1, "b": { "c": 2, "d": 2 } }, { "a": 1, "b": { "c": 2 } }]),
+            Config::new(CompareMode::Strict).max_differences(0),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            r#"... and 3 more differences under "[*].b""#
+        );
+    }
+
+    #[test]
+    fn test_separate_thousands() {
+        assert_eq!(separate_thousands(0), "0");
+        assert_eq!(separate_thousands(999), "999");
+        assert_eq!(separate_thousands(1000), "1,000");
+        assert_eq!(separate_thousands(2317), "2,317");
+        assert_eq!(separate_thousands(1234567), "1,234,567");
+    }
+
+    fn assert_output_eq(actual: Result<(), JsonDiffError>, expected: Result<(), &str>) {
         match (actual, expected) {
             (Ok(()), Ok(())) => {}
 
@@ -636,6 +2056,7 @@ mod tests {
             }
 
             (Err(actual_error), Err(expected_error)) => {
+                let actual_error = actual_error.to_string();
                 let expected_error = expected_error.to_string();
                 if actual_error != expected_error {
                     let mut f = String::new();
@@ -650,11 +2071,11 @@ mod tests {
         }
     }
 
-    fn test_partial_match(lhs: Value, rhs: Value) -> Result<(), String> {
+    fn test_partial_match(lhs: Value, rhs: Value) -> Result<(), JsonDiffError> {
         assert_json_matches_no_panic(&lhs, &rhs, Config::new(CompareMode::Inclusive))
     }
 
-    fn test_exact_match(lhs: Value, rhs: Value) -> Result<(), String> {
+    fn test_exact_match(lhs: Value, rhs: Value) -> Result<(), JsonDiffError> {
         assert_json_matches_no_panic(&lhs, &rhs, Config::new(CompareMode::Strict))
     }
 }
@@ -0,0 +1,83 @@
+use serde_json::Value;
+use std::{fmt, sync::Arc};
+
+/// A custom check for a JSON value, such as "is a valid money amount".
+///
+/// Matchers can be registered for a path with [`Config::matcher_at`] or as a placeholder with
+/// [`Config::placeholder_matcher`]. The explanation returned when a value is rejected is included
+/// in the [`Difference`].
+///
+/// Closures taking a `&Value` and returning `Result<(), String>` implement `Matcher`:
+///
+/// ```
+/// use assert_json_diff::{assert_json_matches, CompareMode, Config};
+/// use serde_json::{json, Value};
+///
+/// fn country_code(actual: &Value) -> Result<(), String> {
+///     match actual.as_str() {
+///         Some("DK") | Some("SE") | Some("NO") => Ok(()),
+///         _ => Err(format!("{} is not a known country code", actual)),
+///     }
+/// }
+///
+/// let config = Config::new(CompareMode::Inclusive)
+///     .matcher_at(".users[*].country", country_code)
+///     .placeholder_matcher("money", |actual: &Value| match actual.as_f64() {
+///         Some(amount) if amount >= 0.0 => Ok(()),
+///         _ => Err("expected a positive amount".to_string()),
+///     });
+///
+/// assert_json_matches!(
+///     json!({
+///         "users": [{ "name": "Bob", "country": "DK" }],
+///         "balance": 10.5,
+///     }),
+///     json!({
+///         "users": [{ "name": "Bob", "country": null }],
+///         "balance": "${money}",
+///     }),
+///     config,
+/// );
+/// ```
+///
+/// [`Config::matcher_at`]: crate::Config::matcher_at
+/// [`Config::placeholder_matcher`]: crate::Config::placeholder_matcher
+/// [`Difference`]: crate::Difference
+pub trait Matcher: Send + Sync {
+    /// Check whether `actual` is accepted, and explain why not otherwise.
+    fn matches(&self, actual: &Value) -> Result<(), String>;
+}
+
+impl<F> Matcher for F
+where
+    F: Fn(&Value) -> Result<(), String> + Send + Sync,
+{
+    fn matches(&self, actual: &Value) -> Result<(), String> {
+        self(actual)
+    }
+}
+
+/// A [`Matcher`] that can be stored in a `Config`.
+#[derive(Clone)]
+pub(crate) struct SharedMatcher(Arc<dyn Matcher>);
+
+impl SharedMatcher {
+    pub(crate) fn new<M>(matcher: M) -> Self
+    where
+        M: Matcher + 'static,
+    {
+        Self(Arc::new(matcher))
+    }
+}
+
+impl Matcher for SharedMatcher {
+    fn matches(&self, actual: &Value) -> Result<(), String> {
+        self.0.matches(actual)
+    }
+}
+
+impl fmt::Debug for SharedMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Matcher").finish()
+    }
+}
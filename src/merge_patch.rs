@@ -0,0 +1,189 @@
+use crate::{assert_json_matches_no_panic, CompareMode, Config, JsonDiffError};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Create an [RFC 7396] JSON Merge Patch that transforms `base` into `target`.
+///
+/// Fields that are missing from `target` are set to `null` in the patch and fields that changed
+/// are replaced, merging objects recursively. Arrays are always replaced entirely.
+///
+/// ```
+/// use assert_json_diff::{apply_merge_patch, merge_patch};
+/// use serde_json::json;
+///
+/// let base = json!({ "name": "Bob", "address": { "city": "Copenhagen", "zip": "2100" } });
+/// let target = json!({ "name": "Bob", "address": { "city": "Aarhus" }, "tags": ["admin"] });
+///
+/// let patch = merge_patch(&base, &target);
+/// assert_eq!(
+///     patch,
+///     json!({ "address": { "city": "Aarhus", "zip": null }, "tags": ["admin"] })
+/// );
+/// assert_eq!(apply_merge_patch(&base, &patch), target);
+/// ```
+///
+/// # Limitations
+///
+/// Merge patches use `null` to mean "remove this field", so they can't set a field to `null`.
+/// If `target` contains `null` fields the patch won't reproduce them exactly, which is a
+/// limitation of the format rather than this function. Use [`json_patch`](crate::json_patch) if
+/// you need that.
+///
+/// [RFC 7396]: https://tools.ietf.org/html/rfc7396
+pub fn merge_patch(base: &Value, target: &Value) -> Value {
+    match (base, target) {
+        (Value::Object(base), Value::Object(target)) => {
+            let mut patch = Map::new();
+            for key in base.keys() {
+                if !target.contains_key(key) {
+                    patch.insert(key.clone(), Value::Null);
+                }
+            }
+            for (key, target) in target {
+                match base.get(key) {
+                    Some(base) if base == target => {}
+                    Some(base) => {
+                        patch.insert(key.clone(), merge_patch(base, target));
+                    }
+                    None => {
+                        patch.insert(key.clone(), target.clone());
+                    }
+                }
+            }
+            Value::Object(patch)
+        }
+        _ => target.clone(),
+    }
+}
+
+/// Apply an [RFC 7396] JSON Merge Patch to `base`.
+///
+/// See [`merge_patch`] for an example.
+///
+/// [RFC 7396]: https://tools.ietf.org/html/rfc7396
+pub fn apply_merge_patch(base: &Value, patch: &Value) -> Value {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        _ => return patch.clone(),
+    };
+
+    let mut result = match base {
+        Value::Object(base) => base.clone(),
+        _ => Map::new(),
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            result.remove(key);
+        } else {
+            let merged = apply_merge_patch(result.get(key).unwrap_or(&Value::Null), value);
+            result.insert(key.clone(), merged);
+        }
+    }
+    Value::Object(result)
+}
+
+/// Applies a merge patch and compares the result to the expected value without panicking.
+///
+/// This is what [`assert_json_merge_patch`](macro.assert_json_merge_patch.html) uses. The error's
+/// `Display` implementation gives the message that would be passed to `panic!`. The patched value
+/// is the left hand side, so [`JsonDiffError::LhsSerialization`] is returned if either `base` or
+/// `patch` can't be converted to JSON.
+pub fn assert_json_merge_patch_no_panic<Base, Patch, Expected>(
+    base: &Base,
+    patch: &Patch,
+    expected: &Expected,
+) -> Result<(), JsonDiffError>
+where
+    Base: Serialize,
+    Patch: Serialize,
+    Expected: Serialize,
+{
+    let base = serde_json::to_value(base).map_err(JsonDiffError::LhsSerialization)?;
+    let patch = serde_json::to_value(patch).map_err(JsonDiffError::LhsSerialization)?;
+
+    let patched = apply_merge_patch(&base, &patch);
+    assert_json_matches_no_panic(&patched, expected, Config::new(CompareMode::Strict))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rfc_examples() {
+        // https://tools.ietf.org/html/rfc7396#appendix-A
+        let examples = vec![
+            (json!({"a":"b"}), json!({"a":"c"}), json!({"a":"c"})),
+            (json!({"a":"b"}), json!({"b":"c"}), json!({"a":"b","b":"c"})),
+            (json!({"a":"b"}), json!({"a":null}), json!({})),
+            (
+                json!({"a":"b","b":"c"}),
+                json!({"a":null}),
+                json!({"b":"c"}),
+            ),
+            (json!({"a":["b"]}), json!({"a":"c"}), json!({"a":"c"})),
+            (json!({"a":"c"}), json!({"a":["b"]}), json!({"a":["b"]})),
+            (
+                json!({"a":{"b":"c"}}),
+                json!({"a":{"b":"d","c":null}}),
+                json!({"a":{"b":"d"}}),
+            ),
+            (json!({"a":[{"b":"c"}]}), json!({"a":[1]}), json!({"a":[1]})),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a":"b"}), json!(["c"]), json!(["c"])),
+            (json!({"a":"foo"}), json!(null), json!(null)),
+            (json!({"a":"foo"}), json!("bar"), json!("bar")),
+            (json!({"e":null}), json!({"a":1}), json!({"e":null,"a":1})),
+            (json!([1, 2]), json!({"a":"b","c":null}), json!({"a":"b"})),
+            (
+                json!({}),
+                json!({"a":{"bb":{"ccc":null}}}),
+                json!({"a":{"bb":{}}}),
+            ),
+        ];
+
+        for (base, patch, expected) in examples {
+            assert_eq!(
+                apply_merge_patch(&base, &patch),
+                expected,
+                "patch: {}",
+                patch
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let examples = vec![
+            (json!({"a":1}), json!({"a":1})),
+            (
+                json!({"a":1,"b":{"c":[1,2]}}),
+                json!({"b":{"c":[1],"d":true}}),
+            ),
+            (json!({"a":{"b":1}}), json!({"a":[1]})),
+            (json!([1]), json!({"a":1})),
+            (json!({"a":1}), json!("a")),
+        ];
+
+        for (base, target) in examples {
+            let patch = merge_patch(&base, &target);
+            assert_eq!(apply_merge_patch(&base, &patch), target, "patch: {}", patch);
+        }
+
+        assert_eq!(merge_patch(&json!({"a":1}), &json!({"a":1})), json!({}));
+        assert_eq!(
+            merge_patch(&json!({"a":{"b":1,"c":2}}), &json!({"a":{"b":1}})),
+            json!({"a":{"c":null}})
+        );
+    }
+
+    #[test]
+    fn test_null_fields_are_removed() {
+        let base = json!({"a":1});
+        let target = json!({"a":null});
+        let patch = merge_patch(&base, &target);
+        assert_eq!(patch, json!({"a":null}));
+        assert_eq!(apply_merge_patch(&base, &patch), json!({}));
+    }
+}
@@ -0,0 +1,433 @@
+use crate::diff::{diff, Difference, DifferenceKind, Key, Path};
+use crate::{CompareMode, Config};
+use serde_json::{json, Value};
+
+/// Create an [RFC 6902] JSON Patch that transforms `lhs` into `rhs`.
+///
+/// The patch is a JSON array of `add`, `remove`, `replace`, `move`, and `copy` operations. Object
+/// fields that were renamed without changing their value become `move` operations and new objects
+/// or arrays that duplicate an unchanged value elsewhere in the document become `copy` operations.
+///
+/// The values are compared exactly, as with [`CompareMode::Strict`], regardless of how you
+/// normally compare them.
+///
+/// ```
+/// use assert_json_diff::json_patch;
+/// use serde_json::json;
+///
+/// let patch = json_patch(
+///     &json!({ "name": "Bob", "tags": ["a", "b"], "address": { "city": "Copenhagen" } }),
+///     &json!({ "name": "Alice", "tags": ["a"], "location": { "city": "Copenhagen" } }),
+/// );
+///
+/// assert_eq!(
+///     patch,
+///     json!([
+///         { "op": "replace", "path": "/name", "value": "Alice" },
+///         { "op": "remove", "path": "/tags/1" },
+///         { "op": "move", "from": "/address", "path": "/location" },
+///     ])
+/// );
+/// ```
+///
+/// [RFC 6902]: https://tools.ietf.org/html/rfc6902
+pub fn json_patch(lhs: &Value, rhs: &Value) -> Value {
+    let diffs = diff(lhs, rhs, &Config::new(CompareMode::Strict));
+
+    let mut operations = vec![];
+    let mut removed = vec![];
+
+    let mut diffs = diffs.iter().peekable();
+    while let Some(difference) = diffs.next() {
+        match difference.kind() {
+            DifferenceKind::MissingFromRhs => {
+                // Array elements are only ever removed from the end, and must be removed from
+                // the back so the indexes of the remaining elements don't shift.
+                let mut run = vec![difference];
+                if is_array_element(difference.path()) {
+                    while let Some(next) = diffs.peek() {
+                        let is_same_array = next.kind() == &DifferenceKind::MissingFromRhs
+                            && is_array_element(next.path())
+                            && parent(next.path()) == parent(difference.path());
+                        if !is_same_array {
+                            break;
+                        }
+                        run.push(next);
+                        diffs.next();
+                    }
+                }
+                for difference in run.into_iter().rev() {
+                    removed.push((operations.len(), difference));
+                    operations.push(Operation::Remove {
+                        path: difference.path().clone(),
+                    });
+                }
+            }
+            DifferenceKind::MissingFromLhs => {
+                let value = difference.rhs().expect("added value").clone();
+                operations.push(Operation::Add {
+                    path: difference.path().clone(),
+                    value,
+                });
+            }
+            _ => {
+                let value = difference.rhs().expect("replaced value").clone();
+                operations.push(Operation::Replace {
+                    path: difference.path().clone(),
+                    value,
+                });
+            }
+        }
+    }
+
+    detect_moves(&mut operations, &removed);
+    detect_copies(&mut operations, lhs, rhs);
+
+    Value::Array(
+        operations
+            .into_iter()
+            .filter_map(|operation| operation.into_json())
+            .collect(),
+    )
+}
+
+#[derive(Debug)]
+enum Operation {
+    Add {
+        path: Path,
+        value: Value,
+    },
+    Remove {
+        path: Path,
+    },
+    Replace {
+        path: Path,
+        value: Value,
+    },
+    Move {
+        from: Path,
+        path: Path,
+    },
+    Copy {
+        from: String,
+        path: Path,
+    },
+    /// A removal that has been turned into a move.
+    Moved,
+}
+
+impl Operation {
+    fn into_json(self) -> Option<Value> {
+        let operation = match self {
+            Operation::Add { path, value } => {
+                json!({ "op": "add", "path": pointer(&path), "value": value })
+            }
+            Operation::Remove { path } => json!({ "op": "remove", "path": pointer(&path) }),
+            Operation::Replace { path, value } => {
+                json!({ "op": "replace", "path": pointer(&path), "value": value })
+            }
+            Operation::Move { from, path } => {
+                json!({ "op": "move", "from": pointer(&from), "path": pointer(&path) })
+            }
+            Operation::Copy { from, path } => {
+                json!({ "op": "copy", "from": from, "path": pointer(&path) })
+            }
+            Operation::Moved => return None,
+        };
+        Some(operation)
+    }
+}
+
+/// Turn pairs of removed and added object fields with the same value into moves.
+///
+/// Only object fields are considered since moving array elements would shift the indexes of the
+/// other elements. The move takes the place of the addition, where the field being moved is still
+/// present since the fields it is removed from are never changed by other operations.
+fn detect_moves(operations: &mut [Operation], removed: &[(usize, &Difference)]) {
+    let mut available = removed
+        .iter()
+        .filter(|(_, difference)| !is_array_element(difference.path()))
+        .collect::<Vec<_>>();
+
+    for idx in 0..operations.len() {
+        let (path, value) = match &operations[idx] {
+            Operation::Add { path, value } if !is_array_element(path) => (path, value),
+            _ => continue,
+        };
+
+        let source = available
+            .iter()
+            .position(|(_, difference)| difference.lhs() == Some(value));
+        if let Some(source) = source {
+            let (removed_idx, difference) = available.remove(source);
+            operations[idx] = Operation::Move {
+                from: difference.path().clone(),
+                path: path.clone(),
+            };
+            operations[*removed_idx] = Operation::Moved;
+        }
+    }
+}
+
+/// Turn additions of objects or arrays that are equal to an unchanged value elsewhere into copies.
+fn detect_copies(operations: &mut [Operation], lhs: &Value, rhs: &Value) {
+    for operation in operations.iter_mut() {
+        let (path, value) = match operation {
+            Operation::Add { path, value } => (path, value),
+            _ => continue,
+        };
+        if !is_non_empty_container(value) {
+            continue;
+        }
+
+        let from = find_unchanged(lhs, rhs, value, &mut String::new());
+        if let Some(from) = from {
+            *operation = Operation::Copy {
+                from,
+                path: path.clone(),
+            };
+        }
+    }
+}
+
+/// Find the pointer to a value in `lhs` that is equal to `needle` and unchanged in `rhs`.
+fn find_unchanged(
+    lhs: &Value,
+    rhs: &Value,
+    needle: &Value,
+    pointer: &mut String,
+) -> Option<String> {
+    if lhs == needle && rhs.pointer(pointer) == Some(needle) {
+        return Some(pointer.clone());
+    }
+
+    let len = pointer.len();
+    let children: Box<dyn Iterator<Item = (String, &Value)>> = match lhs {
+        Value::Array(array) => Box::new(
+            array
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| (idx.to_string(), value)),
+        ),
+        Value::Object(object) => Box::new(object.iter().map(|(key, value)| (escape(key), value))),
+        _ => return None,
+    };
+    for (token, child) in children {
+        pointer.push('/');
+        pointer.push_str(&token);
+        let found = find_unchanged(child, rhs, needle, pointer);
+        pointer.truncate(len);
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+fn is_non_empty_container(value: &Value) -> bool {
+    match value {
+        Value::Array(array) => !array.is_empty(),
+        Value::Object(object) => !object.is_empty(),
+        _ => false,
+    }
+}
+
+fn is_array_element(path: &Path) -> bool {
+    matches!(path.keys().last(), Some(Key::Idx(_)))
+}
+
+fn parent(path: &Path) -> &[Key] {
+    let keys = path.keys();
+    &keys[..keys.len().saturating_sub(1)]
+}
+
+/// Format a path as an [RFC 6901] JSON Pointer.
+///
+/// [RFC 6901]: https://tools.ietf.org/html/rfc6901
+fn pointer(path: &Path) -> String {
+    path.keys()
+        .iter()
+        .map(|key| match key {
+            Key::Idx(idx) => format!("/{}", idx),
+            Key::Field(field) => format!("/{}", escape(field)),
+        })
+        .collect()
+}
+
+fn escape(field: &str) -> String {
+    field.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal implementation of applying a patch, to check that the patches are correct.
+    fn apply(mut target: Value, patch: &Value) -> Value {
+        fn split(pointer: &str) -> (&str, String) {
+            let idx = pointer.rfind('/').unwrap();
+            let token = pointer[idx + 1..].replace("~1", "/").replace("~0", "~");
+            (&pointer[..idx], token)
+        }
+
+        fn remove(target: &mut Value, pointer: &str) -> Value {
+            let (parent, token) = split(pointer);
+            match target.pointer_mut(parent).unwrap() {
+                Value::Object(object) => object.remove(&token).unwrap(),
+                Value::Array(array) => array.remove(token.parse().unwrap()),
+                _ => panic!("can't remove from {}", pointer),
+            }
+        }
+
+        fn add(target: &mut Value, pointer: &str, value: Value) {
+            if pointer.is_empty() {
+                *target = value;
+                return;
+            }
+            let (parent, token) = split(pointer);
+            match target.pointer_mut(parent).unwrap() {
+                Value::Object(object) => {
+                    object.insert(token, value);
+                }
+                Value::Array(array) => array.insert(token.parse().unwrap(), value),
+                _ => panic!("can't add to {}", pointer),
+            }
+        }
+
+        for operation in patch.as_array().unwrap() {
+            let path = operation["path"].as_str().unwrap();
+            match operation["op"].as_str().unwrap() {
+                "add" => add(&mut target, path, operation["value"].clone()),
+                "remove" => {
+                    remove(&mut target, path);
+                }
+                "replace" => *target.pointer_mut(path).unwrap() = operation["value"].clone(),
+                "move" => {
+                    let value = remove(&mut target, operation["from"].as_str().unwrap());
+                    add(&mut target, path, value);
+                }
+                "copy" => {
+                    let from = operation["from"].as_str().unwrap();
+                    let value = target.pointer(from).unwrap().clone();
+                    add(&mut target, path, value);
+                }
+                op => panic!("unknown op {}", op),
+            }
+        }
+        target
+    }
+
+    fn assert_round_trip(lhs: Value, rhs: Value) -> Value {
+        let patch = json_patch(&lhs, &rhs);
+        assert_eq!(apply(lhs, &patch), rhs, "patch: {}", patch);
+        patch
+    }
+
+    #[test]
+    fn test_basic_operations() {
+        assert_eq!(
+            assert_round_trip(json!({ "a": 1 }), json!({ "a": 1 })),
+            json!([])
+        );
+
+        assert_eq!(
+            assert_round_trip(json!({ "a": 1 }), json!({ "a": 2, "b": 3 })),
+            json!([
+                { "op": "replace", "path": "/a", "value": 2 },
+                { "op": "add", "path": "/b", "value": 3 },
+            ])
+        );
+
+        assert_eq!(
+            assert_round_trip(json!(1), json!("1")),
+            json!([{ "op": "replace", "path": "", "value": "1" }])
+        );
+
+        assert_eq!(
+            assert_round_trip(json!({ "a/b": { "c~d": 1 } }), json!({ "a/b": {} })),
+            json!([{ "op": "remove", "path": "/a~1b/c~0d" }])
+        );
+    }
+
+    #[test]
+    fn test_arrays() {
+        assert_eq!(
+            assert_round_trip(json!([1, 2, 3, 4]), json!([1, 5])),
+            json!([
+                { "op": "replace", "path": "/1", "value": 5 },
+                { "op": "remove", "path": "/3" },
+                { "op": "remove", "path": "/2" },
+            ])
+        );
+
+        assert_eq!(
+            assert_round_trip(json!([1]), json!([1, 2, 3])),
+            json!([
+                { "op": "add", "path": "/1", "value": 2 },
+                { "op": "add", "path": "/2", "value": 3 },
+            ])
+        );
+
+        assert_round_trip(
+            json!({ "a": [[1, 2, 3], [4, 5]], "b": [{ "c": [1, 2] }] }),
+            json!({ "a": [[1], [4, 5, 6]], "b": [{ "c": [] }, {}] }),
+        );
+    }
+
+    #[test]
+    fn test_moves() {
+        assert_eq!(
+            assert_round_trip(
+                json!({ "a": { "b": [1, 2] }, "c": 1 }),
+                json!({ "a": {}, "c": 1, "d": [1, 2] }),
+            ),
+            json!([{ "op": "move", "from": "/a/b", "path": "/d" }])
+        );
+
+        assert_eq!(
+            assert_round_trip(
+                json!({ "old": "x", "y": [1] }),
+                json!({ "new": "x", "y": [] })
+            ),
+            json!([
+                { "op": "remove", "path": "/y/0" },
+                { "op": "move", "from": "/old", "path": "/new" },
+            ])
+        );
+
+        // array elements aren't moved
+        assert_eq!(
+            assert_round_trip(json!({ "a": [1, 2] }), json!({ "a": [1], "b": 2 })),
+            json!([
+                { "op": "remove", "path": "/a/1" },
+                { "op": "add", "path": "/b", "value": 2 },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_copies() {
+        assert_eq!(
+            assert_round_trip(
+                json!({ "a": { "b": [1, 2] } }),
+                json!({ "a": { "b": [1, 2] }, "c": { "b": [1, 2] } }),
+            ),
+            json!([{ "op": "copy", "from": "/a", "path": "/c" }])
+        );
+
+        // scalars aren't copied
+        assert_eq!(
+            assert_round_trip(json!({ "a": 1 }), json!({ "a": 1, "b": 1 })),
+            json!([{ "op": "add", "path": "/b", "value": 1 }])
+        );
+
+        // changed values aren't copied
+        assert_eq!(
+            assert_round_trip(json!({ "a": [1] }), json!({ "a": [2], "b": [1] })),
+            json!([
+                { "op": "replace", "path": "/a/0", "value": 2 },
+                { "op": "add", "path": "/b", "value": [1] },
+            ])
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use crate::diff::KeyRef;
+use std::fmt;
+
+/// A pattern that matches paths in a JSON document, such as `.data.users[*].etag`.
+///
+/// See [`Config::ignore_path`](crate::Config::ignore_path) for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PathPattern {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Field(String),
+    AnyField,
+    Idx(usize),
+    AnyIdx,
+    Descendants,
+}
+
+impl PathPattern {
+    pub(crate) fn parse(source: &str) -> Result<Self, String> {
+        let mut segments = vec![];
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => {
+                    if chars.peek() == Some(&'.') {
+                        chars.next();
+                        segments.push(Segment::Descendants);
+                        // `..name` is short for `...name`, but `..[0]` is also allowed
+                        if chars.peek() == Some(&'[') {
+                            continue;
+                        }
+                    }
+
+                    let mut name = String::new();
+                    while let Some(c) = chars.peek() {
+                        if *c == '.' || *c == '[' {
+                            break;
+                        }
+                        name.push(*c);
+                        chars.next();
+                    }
+
+                    match name.as_str() {
+                        "" => return Err("expected a field name after `.`".to_string()),
+                        "*" => segments.push(Segment::AnyField),
+                        _ => segments.push(Segment::Field(name)),
+                    }
+                }
+                '[' => {
+                    let mut inner = String::new();
+                    let mut in_string = false;
+                    let mut escaped = false;
+                    loop {
+                        match chars.next() {
+                            None => return Err("unclosed `[`".to_string()),
+                            Some(']') if !in_string => break,
+                            Some(c) => {
+                                if in_string && !escaped && c == '"' {
+                                    in_string = false;
+                                } else if !in_string && c == '"' {
+                                    in_string = true;
+                                }
+                                escaped = in_string && !escaped && c == '\\';
+                                inner.push(c);
+                            }
+                        }
+                    }
+
+                    let segment = if inner == "*" {
+                        Segment::AnyIdx
+                    } else if inner.starts_with('"') {
+                        let name = serde_json::from_str::<String>(&inner)
+                            .map_err(|_| format!("invalid field name `{}`", inner))?;
+                        Segment::Field(name)
+                    } else {
+                        let idx = inner
+                            .parse()
+                            .map_err(|_| format!("invalid array index `{}`", inner))?;
+                        Segment::Idx(idx)
+                    };
+                    segments.push(segment);
+                }
+                other => {
+                    return Err(format!(
+                        "expected `.` or `[` but found `{}`, paths must start with one of them",
+                        other
+                    ))
+                }
+            }
+        }
+
+        if segments.is_empty() {
+            return Err("empty path".to_string());
+        }
+        if segments.last() == Some(&Segment::Descendants) {
+            return Err("`..` must be followed by a field or an index".to_string());
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    pub(crate) fn matches(&self, keys: &[KeyRef<'_>]) -> bool {
+        matches(&self.segments, keys)
+    }
+}
+
+fn matches(segments: &[Segment], keys: &[KeyRef<'_>]) -> bool {
+    match segments.split_first() {
+        None => keys.is_empty(),
+        Some((Segment::Descendants, rest)) => {
+            (0..=keys.len()).any(|skip| matches(rest, &keys[skip..]))
+        }
+        Some((segment, rest)) => match keys.split_first() {
+            Some((key, keys)) => segment.matches(key) && matches(rest, keys),
+            None => false,
+        },
+    }
+}
+
+impl Segment {
+    fn matches(&self, key: &KeyRef<'_>) -> bool {
+        match (self, key) {
+            (Segment::Field(name), KeyRef::Field(key)) => name == key,
+            (Segment::AnyField, KeyRef::Field(_)) => true,
+            (Segment::Idx(idx), KeyRef::Idx(key)) => idx == key,
+            (Segment::AnyIdx, KeyRef::Idx(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys<'a>(path: &[&'a str]) -> Vec<KeyRef<'a>> {
+        path.iter()
+            .map(|key| match key.parse() {
+                Ok(idx) => KeyRef::Idx(idx),
+                Err(_) => KeyRef::Field(key),
+            })
+            .collect()
+    }
+
+    fn is_match(pattern: &str, path: &[&str]) -> bool {
+        PathPattern::parse(pattern).unwrap().matches(&keys(path))
+    }
+
+    #[test]
+    fn test_fields_and_indexes() {
+        assert!(is_match(".a", &["a"]));
+        assert!(!is_match(".a", &["b"]));
+        assert!(!is_match(".a", &["a", "b"]));
+        assert!(!is_match(".a.b", &["a"]));
+        assert!(is_match(".a[1].b", &["a", "1", "b"]));
+        assert!(!is_match(".a[1].b", &["a", "2", "b"]));
+        assert!(is_match("[0]", &["0"]));
+        assert!(is_match(r#"["a.b"]["[c]"]"#, &["a.b", "[c]"]));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(is_match(
+            ".data.users[*].etag",
+            &["data", "users", "3", "etag"]
+        ));
+        assert!(!is_match(
+            ".data.users[*].etag",
+            &["data", "users", "x", "etag"]
+        ));
+        assert!(is_match(".*.id", &["user", "id"]));
+        assert!(!is_match(".*.id", &["0", "id"]));
+        assert!(!is_match(".*", &[]));
+    }
+
+    #[test]
+    fn test_descendants() {
+        assert!(is_match("..etag", &["etag"]));
+        assert!(is_match("..etag", &["a", "0", "b", "etag"]));
+        assert!(!is_match("..etag", &["etag", "a"]));
+        assert!(is_match(".data..id", &["data", "users", "0", "id"]));
+        assert!(!is_match(".data..id", &["other", "id"]));
+        assert!(is_match("..[0]", &["a", "0"]));
+        assert!(is_match("..users..id", &["a", "users", "2", "id"]));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(PathPattern::parse("").is_err());
+        assert!(PathPattern::parse("a").is_err());
+        assert!(PathPattern::parse(".").is_err());
+        assert!(PathPattern::parse(".a..").is_err());
+        assert!(PathPattern::parse("...a").is_err());
+        assert!(PathPattern::parse(".a[").is_err());
+        assert!(PathPattern::parse(".a[x]").is_err());
+    }
+}
@@ -0,0 +1,300 @@
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The delimiters that mark a string as a placeholder, such as `${` and `}`.
+#[derive(Debug, Clone)]
+pub(crate) struct PlaceholderSyntax {
+    prefix: String,
+    suffix: String,
+    regexes: RegexCache,
+}
+
+impl PlaceholderSyntax {
+    pub(crate) fn new(prefix: &str, suffix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+            regexes: RegexCache::default(),
+        }
+    }
+
+    /// Parse `value` as a placeholder, if it is a string using this syntax.
+    pub(crate) fn parse<'a>(&'a self, value: &'a Value) -> Option<Placeholder<'a>> {
+        let source = value.as_str()?;
+        let inner = source
+            .strip_prefix(self.prefix.as_str())?
+            .strip_suffix(self.suffix.as_str())?;
+
+        let (name, arg) = match inner.find(':') {
+            Some(idx) => (&inner[..idx], Some(&inner[idx + 1..])),
+            None => (inner, None),
+        };
+
+        Some(Placeholder {
+            source,
+            name,
+            arg,
+            regexes: &self.regexes,
+        })
+    }
+}
+
+impl Default for PlaceholderSyntax {
+    fn default() -> Self {
+        Self::new("${", "}")
+    }
+}
+
+/// The compiled patterns of `${regex:...}` placeholders.
+///
+/// Shared between clones of a config so each pattern is only compiled once, rather than once for
+/// every value it is checked against.
+#[derive(Debug, Clone, Default)]
+struct RegexCache(Arc<Mutex<HashMap<String, Result<Regex, String>>>>);
+
+impl RegexCache {
+    fn get(&self, pattern: &str) -> Result<Regex, String> {
+        let mut regexes = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(regex) = regexes.get(pattern) {
+            return regex.clone();
+        }
+        let regex =
+            Regex::new(pattern).map_err(|err| format!("invalid regex `{}`: {}", pattern, err));
+        regexes.insert(pattern.to_string(), regex.clone());
+        regex
+    }
+}
+
+/// A placeholder in the expected JSON, such as `${any_uuid}` or `${regex:^[a-z]+$}`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Placeholder<'a> {
+    pub(crate) source: &'a str,
+    pub(crate) name: &'a str,
+    pub(crate) arg: Option<&'a str>,
+    regexes: &'a RegexCache,
+}
+
+impl<'a> Placeholder<'a> {
+    /// Check whether `actual` is accepted by the placeholder, and explain why not otherwise.
+    pub(crate) fn check(&self, actual: &Value) -> Result<(), String> {
+        match (self.name, self.arg) {
+            ("any", None) => Ok(()),
+            ("any_string", None) => expect(actual.is_string(), "a string"),
+            ("any_number", None) => expect(actual.is_number(), "a number"),
+            ("any_bool", None) => expect(actual.is_boolean(), "a boolean"),
+            ("any_uuid", None) => expect(actual.as_str().is_some_and(is_uuid), "a UUID"),
+            ("iso8601", None) => expect(
+                actual.as_str().is_some_and(is_iso8601),
+                "an ISO 8601 date or date-time",
+            ),
+            ("non_empty", None) => {
+                let is_non_empty = match actual {
+                    Value::String(s) => !s.is_empty(),
+                    Value::Array(array) => !array.is_empty(),
+                    Value::Object(object) => !object.is_empty(),
+                    Value::Null | Value::Bool(_) | Value::Number(_) => false,
+                };
+                expect(is_non_empty, "a non-empty string, array, or object")
+            }
+            ("regex", Some(pattern)) => {
+                let regex = self.regexes.get(pattern)?;
+                let is_match = actual.as_str().is_some_and(|s| regex.is_match(s));
+                expect(is_match, &format!("a string matching `{}`", pattern))
+            }
+            (name, _) => Err(format!("unknown placeholder `{}`", name)),
+        }
+    }
+}
+
+fn expect(is_ok: bool, expected: &str) -> Result<(), String> {
+    if is_ok {
+        Ok(())
+    } else {
+        Err(format!("expected {}", expected))
+    }
+}
+
+fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.char_indices().all(|(idx, c)| match idx {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Is `s` an ISO 8601 date, `2021-01-23`, or an RFC 3339 date-time, `2021-01-23T12:00:00Z`?
+fn is_iso8601(s: &str) -> bool {
+    let (date, time) = match s.find(['T', 't']) {
+        Some(idx) => (&s[..idx], Some(&s[idx + 1..])),
+        None => (s, None),
+    };
+
+    let is_valid_time = match time {
+        Some(time) => is_time(time),
+        None => true,
+    };
+    is_date(date) && is_valid_time
+}
+
+fn is_date(s: &str) -> bool {
+    let parts = split_numbers(s, '-');
+    match parts.as_slice() {
+        [Some((year, 4)), Some((month, 2)), Some((day, 2))] => {
+            let leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+            let days_in_month = match month {
+                1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+                4 | 6 | 9 | 11 => 30,
+                2 if leap_year => 29,
+                2 => 28,
+                _ => return false,
+            };
+            (1..=days_in_month).contains(day)
+        }
+        _ => false,
+    }
+}
+
+fn is_time(s: &str) -> bool {
+    let (time, offset) = if let Some(time) = s.strip_suffix(['Z', 'z']) {
+        (time, None)
+    } else {
+        match s.rfind(['+', '-']) {
+            Some(idx) => (&s[..idx], Some(&s[idx + 1..])),
+            None => return false,
+        }
+    };
+
+    let time = match time.find('.') {
+        Some(idx) => {
+            let fraction = &time[idx + 1..];
+            if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return false;
+            }
+            &time[..idx]
+        }
+        None => time,
+    };
+
+    let is_valid_time = matches!(
+        split_numbers(time, ':').as_slice(),
+        [Some((hour, 2)), Some((minute, 2)), Some((second, 2))]
+            if *hour < 24 && *minute < 60 && *second <= 60
+    );
+    let is_valid_offset = match offset {
+        Some(offset) => matches!(
+            split_numbers(offset, ':').as_slice(),
+            [Some((hour, 2)), Some((minute, 2))] if *hour < 24 && *minute < 60
+        ),
+        None => true,
+    };
+
+    is_valid_time && is_valid_offset
+}
+
+/// Split `s` on `separator` and parse each part as a number, along with how many digits it had.
+fn split_numbers(s: &str, separator: char) -> Vec<Option<(u32, usize)>> {
+    s.split(separator)
+        .map(|part| {
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            part.parse().ok().map(|number| (number, part.len()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn check(placeholder: &str, actual: Value) -> Result<(), String> {
+        let placeholder = json!(placeholder);
+        PlaceholderSyntax::default()
+            .parse(&placeholder)
+            .unwrap()
+            .check(&actual)
+    }
+
+    #[test]
+    fn test_parse() {
+        let syntax = PlaceholderSyntax::default();
+        assert!(syntax.parse(&json!("any")).is_none());
+        assert!(syntax.parse(&json!(1)).is_none());
+        assert!(syntax.parse(&json!("${any")).is_none());
+
+        let value = json!("${regex:^a:b$}");
+        let placeholder = syntax.parse(&value).unwrap();
+        assert_eq!(placeholder.name, "regex");
+        assert_eq!(placeholder.arg, Some("^a:b$"));
+        assert_eq!(placeholder.source, "${regex:^a:b$}");
+
+        let syntax = PlaceholderSyntax::new("{{", "}}");
+        assert!(syntax.parse(&json!("${any}")).is_none());
+        assert_eq!(syntax.parse(&json!("{{any}}")).unwrap().name, "any");
+    }
+
+    #[test]
+    fn test_builtin_placeholders() {
+        assert!(check("${any}", json!(null)).is_ok());
+        assert!(check("${any_string}", json!("")).is_ok());
+        assert!(check("${any_string}", json!(1)).is_err());
+        assert!(check("${any_number}", json!(1.5)).is_ok());
+        assert!(check("${any_number}", json!("1")).is_err());
+        assert!(check("${any_bool}", json!(false)).is_ok());
+        assert!(check("${any_bool}", json!(null)).is_err());
+        assert!(check("${non_empty}", json!([1])).is_ok());
+        assert!(check("${non_empty}", json!({})).is_err());
+        assert!(check("${non_empty}", json!("")).is_err());
+        assert!(check("${regex:^[a-z]+$}", json!("abc")).is_ok());
+        assert!(check("${regex:^[a-z]+$}", json!("abc1")).is_err());
+        assert!(check("${regex:^[a-z]+$}", json!(1)).is_err());
+        assert_eq!(
+            check("${unknown}", json!(1)),
+            Err("unknown placeholder `unknown`".to_string())
+        );
+        assert!(check("${regex:(}", json!("("))
+            .unwrap_err()
+            .starts_with("invalid regex `(`"));
+    }
+
+    #[test]
+    fn test_regexes_are_compiled_once() {
+        let syntax = PlaceholderSyntax::default();
+        let value = json!("${regex:^[a-z]+$}");
+        for actual in [json!("abc"), json!("def"), json!("1")] {
+            let _ = syntax.parse(&value).unwrap().check(&actual);
+        }
+        let _ = syntax.clone().parse(&value).unwrap().check(&json!("ghi"));
+        assert_eq!(syntax.regexes.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_uuid() {
+        assert!(is_uuid("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(is_uuid("67E55044-10B1-426F-9247-BB680E5FE0C8"));
+        assert!(!is_uuid("67e55044-10b1-426f-9247-bb680e5fe0c"));
+        assert!(!is_uuid("67e55044x10b1-426f-9247-bb680e5fe0c8"));
+        assert!(!is_uuid("g7e55044-10b1-426f-9247-bb680e5fe0c8"));
+    }
+
+    #[test]
+    fn test_iso8601() {
+        assert!(is_iso8601("2021-01-23"));
+        assert!(is_iso8601("2021-01-23T12:00:00Z"));
+        assert!(is_iso8601("2021-01-23T12:00:00.123456+02:00"));
+        assert!(is_iso8601("2020-02-29T23:59:60-08:00"));
+        assert!(!is_iso8601("2021-02-29"));
+        assert!(!is_iso8601("2021-13-01"));
+        assert!(!is_iso8601("21-01-23"));
+        assert!(!is_iso8601("2021-01-23T12:00:00"));
+        assert!(!is_iso8601("2021-01-23T24:00:00Z"));
+        assert!(!is_iso8601("2021-01-23T12:00Z"));
+        assert!(!is_iso8601("2021-01-23T12:00:00.Z"));
+        assert!(!is_iso8601("yesterday"));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::diff::{KeyRef, PathStack};
+use crate::path_pattern::PathPattern;
+use crate::Config;
+use serde_json::Value;
+use std::{borrow::Cow, fmt, sync::Arc};
+
+/// A rule for replacing volatile or secret values with a placeholder.
+#[derive(Clone, Debug)]
+pub(crate) struct Redaction {
+    label: String,
+    rule: Rule,
+}
+
+#[derive(Clone)]
+enum Rule {
+    Path(PathPattern),
+    Value(Arc<dyn Fn(&Value) -> bool + Send + Sync>),
+}
+
+impl Redaction {
+    pub(crate) fn path(pattern: PathPattern, label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            rule: Rule::Path(pattern),
+        }
+    }
+
+    pub(crate) fn value<F>(label: &str, predicate: F) -> Self
+    where
+        F: Fn(&Value) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            label: label.to_string(),
+            rule: Rule::Value(Arc::new(predicate)),
+        }
+    }
+
+    fn applies(&self, path: &[KeyRef<'_>], value: &Value) -> bool {
+        match &self.rule {
+            Rule::Path(pattern) => pattern.matches(path),
+            Rule::Value(predicate) => predicate(value),
+        }
+    }
+
+    fn placeholder(&self) -> Value {
+        Value::String(format!("[redacted:{}]", self.label))
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule::Path(pattern) => f.debug_tuple("Path").field(pattern).finish(),
+            Rule::Value(_) => f.debug_tuple("Value").finish(),
+        }
+    }
+}
+
+/// Replace the values matched by the config's redactions with placeholders.
+///
+/// Matched values are replaced as a whole, so nothing inside them is visited. The value is only
+/// cloned if something is redacted.
+pub(crate) fn redact<'v>(value: &'v Value, config: &Config) -> Cow<'v, Value> {
+    if config.redactions.is_empty() {
+        return Cow::Borrowed(value);
+    }
+    let mut path = PathStack::default();
+    match redacted(value, &config.redactions, &mut path) {
+        Some(redacted) => Cow::Owned(redacted),
+        None => Cow::Borrowed(value),
+    }
+}
+
+/// The redacted version of `value`, or `None` if nothing in it is redacted.
+fn redacted<'a>(
+    value: &'a Value,
+    redactions: &[Redaction],
+    path: &mut PathStack<'a>,
+) -> Option<Value> {
+    if let Some(redaction) = redactions.iter().find(|r| r.applies(path.keys(), value)) {
+        return Some(redaction.placeholder());
+    }
+
+    match value {
+        Value::Array(array) => {
+            let mut changed: Option<Vec<Value>> = None;
+            for (idx, item) in array.iter().enumerate() {
+                path.push(KeyRef::Idx(idx));
+                let item = redacted(item, redactions, path);
+                path.pop();
+                if let Some(item) = item {
+                    changed.get_or_insert_with(|| array.clone())[idx] = item;
+                }
+            }
+            changed.map(Value::Array)
+        }
+        Value::Object(object) => {
+            let mut changed = None;
+            for (key, item) in object {
+                path.push(KeyRef::Field(key));
+                let item = redacted(item, redactions, path);
+                path.pop();
+                if let Some(item) = item {
+                    changed
+                        .get_or_insert_with(|| object.clone())
+                        .insert(key.clone(), item);
+                }
+            }
+            changed.map(Value::Object)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CompareMode;
+    use serde_json::json;
+
+    #[test]
+    fn test_redacts_paths() {
+        let config = Config::new(CompareMode::Strict)
+            .redact_path("..token", "token")
+            .redact_path(".users[*].created_at", "timestamp");
+
+        let value = json!({
+            "token": "abc",
+            "users": [{ "id": 1, "created_at": "2021-01-23" }, { "id": 2 }],
+            "session": { "token": { "value": "def" } },
+        });
+        assert_eq!(
+            redact(&value, &config).into_owned(),
+            json!({
+                "token": "[redacted:token]",
+                "users": [{ "id": 1, "created_at": "[redacted:timestamp]" }, { "id": 2 }],
+                "session": { "token": "[redacted:token]" },
+            })
+        );
+    }
+
+    #[test]
+    fn test_redacts_values() {
+        let config = Config::new(CompareMode::Strict).redact_values("uuid", |value: &Value| {
+            value.as_str().is_some_and(|s| s.len() == 36)
+        });
+
+        let value = json!(["67e55044-10b1-426f-9247-bb680e5fe0c8", "short", 1]);
+        assert_eq!(
+            redact(&value, &config).into_owned(),
+            json!(["[redacted:uuid]", "short", 1])
+        );
+    }
+
+    #[test]
+    fn test_only_clones_when_something_is_redacted() {
+        let config = Config::new(CompareMode::Strict).redact_path(".secret", "secret");
+
+        let value = json!({ "public": [1, 2] });
+        assert!(matches!(redact(&value, &config), Cow::Borrowed(_)));
+
+        let value = json!({ "secret": 1 });
+        assert!(matches!(redact(&value, &config), Cow::Owned(_)));
+    }
+}
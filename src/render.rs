@@ -0,0 +1,301 @@
+use crate::diff::{Difference, DifferenceKind};
+use crate::CompareMode;
+use similar::{ChangeTag, DiffOp, InlineChange, TextDiff};
+use std::io::IsTerminal;
+
+/// How colored diffs are laid out. See [`Config::color_diff`](crate::Config::color_diff).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DiffStyle {
+    /// Changed lines are shown below each other, prefixed with `-` and `+`.
+    Unified,
+    /// The two values are shown next to each other, line by line.
+    SideBySide,
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const BOLD: &str = "\x1b[1m";
+const HIGHLIGHT: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether failures should be rendered with colors.
+///
+/// Colors are disabled if the [`NO_COLOR`](https://no-color.org) environment variable is set to a
+/// non-empty value or if stdout isn't a terminal.
+pub fn colors_enabled() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color && std::io::stdout().is_terminal()
+}
+
+impl Difference {
+    /// Render the difference with a colored diff of the pretty printed values.
+    ///
+    /// Changed lines are red and green and the changed parts of those lines are highlighted.
+    /// Differences that don't have two values to compare, such as missing values, are rendered the
+    /// same as with `Display`.
+    ///
+    /// This always includes colors. Use [`colors_enabled`] to check whether you should.
+    pub fn to_colored_string(&self, style: DiffStyle) -> String {
+        let (lhs, rhs) = match (self.lhs(), self.rhs()) {
+            (Some(lhs), Some(rhs))
+                if !matches!(self.kind(), DifferenceKind::MatcherRejected { .. }) =>
+            {
+                (lhs, rhs)
+            }
+            _ => return self.to_string(),
+        };
+
+        let mut header = String::new();
+        self.write_not_equal(&mut header)
+            .expect("writing to a String can't fail");
+
+        // the expected value is shown as the old one, so `-` means "expected but not there"
+        let ((old_label, old), (new_label, new)) = match self.compare_mode() {
+            CompareMode::Inclusive => (("expected", rhs), ("actual", lhs)),
+            CompareMode::Strict => (("lhs", lhs), ("rhs", rhs)),
+        };
+        let old = serde_json::to_string_pretty(old).unwrap();
+        let new = serde_json::to_string_pretty(new).unwrap();
+        let diff = TextDiff::from_lines(&old, &new);
+
+        let body = match style {
+            DiffStyle::Unified => unified(&diff, old_label, new_label),
+            DiffStyle::SideBySide => side_by_side(&diff, old_label, new_label),
+        };
+
+        format!("{}{}{}{}", BOLD, header.trim_end(), RESET, body)
+    }
+}
+
+fn unified<'a>(diff: &'a TextDiff<'a, 'a, 'a, str>, old_label: &str, new_label: &str) -> String {
+    let mut out = format!(
+        "\n    {}- {}{}\n    {}+ {}{}",
+        RED, old_label, RESET, GREEN, new_label, RESET
+    );
+
+    for op in diff.ops() {
+        for change in diff.iter_inline_changes(op) {
+            let (sign, color) = match change.tag() {
+                ChangeTag::Delete => ("-", RED),
+                ChangeTag::Insert => ("+", GREEN),
+                ChangeTag::Equal => (" ", ""),
+            };
+            let (line, _) = render_line(&change, color);
+            out.push_str(&format!("\n    {}{}{} {}", color, sign, reset(color), line));
+        }
+    }
+
+    out
+}
+
+fn side_by_side<'a>(
+    diff: &'a TextDiff<'a, 'a, 'a, str>,
+    old_label: &str,
+    new_label: &str,
+) -> String {
+    let mut rows = vec![(
+        Some((format!("{}{}{}", RED, old_label, RESET), old_label.len())),
+        Some((format!("{}{}{}", GREEN, new_label, RESET), new_label.len())),
+    )];
+
+    for op in diff.ops() {
+        let mut left = vec![];
+        let mut right = vec![];
+        for change in diff.iter_inline_changes(op) {
+            match change.tag() {
+                ChangeTag::Delete => left.push(render_line(&change, RED)),
+                ChangeTag::Insert => right.push(render_line(&change, GREEN)),
+                ChangeTag::Equal => {
+                    left.push(render_line(&change, ""));
+                    right.push(render_line(&change, ""));
+                }
+            }
+        }
+
+        // pair up the removed and inserted lines of a replacement
+        let len = match op {
+            DiffOp::Equal { .. } => left.len(),
+            _ => left.len().max(right.len()),
+        };
+        let mut left = left.into_iter();
+        let mut right = right.into_iter();
+        for _ in 0..len {
+            rows.push((left.next(), right.next()));
+        }
+    }
+
+    let width = rows
+        .iter()
+        .filter_map(|(left, _)| left.as_ref().map(|(_, width)| *width))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for (left, right) in rows {
+        let (left, left_width) = left.unwrap_or_default();
+        let (right, _) = right.unwrap_or_default();
+        let padding = " ".repeat(width - left_width);
+        let row = format!("    {}{} | {}", left, padding, right);
+        out.push('\n');
+        out.push_str(row.trim_end());
+    }
+    out
+}
+
+/// Render a line with its changed parts highlighted, along with its width without colors.
+fn render_line(change: &InlineChange<str>, color: &str) -> (String, usize) {
+    let mut line = String::new();
+    let mut width = 0;
+
+    for (highlighted, value) in change.iter_strings_lossy() {
+        let value = value.trim_end_matches('\n');
+        if value.is_empty() {
+            continue;
+        }
+        width += value.chars().count();
+
+        if highlighted {
+            line.push_str(&format!("{}{}{}{}", color, HIGHLIGHT, value, RESET));
+        } else {
+            line.push_str(&format!("{}{}{}", color, value, reset(color)));
+        }
+    }
+
+    (line, width)
+}
+
+fn reset(color: &str) -> &'static str {
+    if color.is_empty() {
+        ""
+    } else {
+        RESET
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{diff_json, CompareMode, Config};
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn render(lhs: Value, rhs: Value, config: Config, style: DiffStyle) -> String {
+        diff_json(&lhs, &rhs, &config)
+            .iter()
+            .map(|difference| difference.to_colored_string(style))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn strip_colors(s: &str) -> String {
+        let mut out = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_unified() {
+        let rendered = render(
+            json!({ "a": { "b": 1, "c": [true, "foo"] } }),
+            json!({ "a": { "b": 1, "c": "foo" } }),
+            Config::new(CompareMode::Strict),
+            DiffStyle::Unified,
+        );
+
+        assert_eq!(
+            strip_colors(&rendered),
+            r#"json atoms at path ".a.c" are not equal:
+    - lhs
+    + rhs
+    - [
+    -   true,
+    -   "foo"
+    - ]
+    + "foo""#
+        );
+        assert!(rendered.starts_with(BOLD));
+    }
+
+    #[test]
+    fn test_changed_parts_are_highlighted() {
+        let rendered = render(
+            json!({ "a": "hello world" }),
+            json!({ "a": "hello there" }),
+            Config::new(CompareMode::Inclusive),
+            DiffStyle::Unified,
+        );
+
+        assert_eq!(
+            strip_colors(&rendered),
+            r#"json atoms at path ".a" are not equal:
+    - expected
+    + actual
+    - "hello there"
+    + "hello world""#
+        );
+        assert!(rendered.contains(&format!("{}{}there", RED, HIGHLIGHT)));
+        assert!(rendered.contains(&format!("{}{}world", GREEN, HIGHLIGHT)));
+        assert!(rendered.contains(&format!("{}\"hello {}", GREEN, RESET)));
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        let rendered = render(
+            json!([{ "id": 1, "name": "Bob" }]),
+            json!([{ "id": 2, "name": "Bob", "admin": true }]),
+            Config::new(CompareMode::Strict),
+            DiffStyle::SideBySide,
+        );
+
+        assert_eq!(
+            strip_colors(&rendered),
+            r#"json atoms at path "[0].id" are not equal:
+    lhs | rhs
+    1   | 2
+
+json atom at path "[0].admin" is missing from lhs:
+    rhs:
+        true"#
+        );
+
+        let rendered = render(
+            json!({ "a": { "id": 1, "name": "Bob" } }),
+            json!({ "a": [1] }),
+            Config::new(CompareMode::Strict),
+            DiffStyle::SideBySide,
+        );
+
+        assert_eq!(
+            strip_colors(&rendered),
+            r#"json atoms at path ".a" are not equal:
+    lhs             | rhs
+    {               | [
+      "id": 1,      |   1
+      "name": "Bob" | ]
+    }               |"#
+        );
+    }
+
+    #[test]
+    fn test_without_two_values() {
+        let rendered = render(
+            json!({}),
+            json!({ "a": 1 }),
+            Config::new(CompareMode::Inclusive),
+            DiffStyle::Unified,
+        );
+        assert_eq!(
+            rendered,
+            r#"json atom at path ".a" is missing from actual:
+    expected:
+        1"#
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{compare, redact, Config, JsonDiffError, ParseError};
+use serde::Serialize;
+use std::{fs, io, path::Path};
+
+/// The environment variable that makes snapshot assertions rewrite their snapshots.
+const UPDATE_VAR: &str = "UPDATE_JSON_SNAPSHOTS";
+
+/// Compares a value to a JSON snapshot file without panicking.
+///
+/// The value is "actual" and the snapshot is "expected" when using
+/// [`CompareMode::Inclusive`](crate::CompareMode::Inclusive), so ignored paths, numeric modes,
+/// and the rest of the [`Config`] apply as usual. If the file doesn't exist it is created with the
+/// value, pretty printed. If the `UPDATE_JSON_SNAPSHOTS` environment variable is set to `1` the
+/// file is rewritten with the value instead of being compared. Values redacted by the config, see
+/// [`Config::redact_path`], are written as their placeholders.
+///
+/// Relative paths are relative to the current directory.
+/// [`assert_json_snapshot`](macro.assert_json_snapshot.html) resolves them relative to the root
+/// of the crate instead.
+pub fn assert_json_snapshot_no_panic<T, P>(
+    value: &T,
+    path: P,
+    config: Config,
+) -> Result<(), JsonDiffError>
+where
+    T: Serialize + ?Sized,
+    P: AsRef<Path>,
+{
+    let update = std::env::var_os(UPDATE_VAR).is_some_and(|var| var == "1");
+    check(value, path.as_ref(), &config, update)
+}
+
+fn check<T>(value: &T, path: &Path, config: &Config, update: bool) -> Result<(), JsonDiffError>
+where
+    T: Serialize + ?Sized,
+{
+    let actual = serde_json::to_value(value).map_err(JsonDiffError::LhsSerialization)?;
+    // redact before writing so secrets never end up in the snapshot
+    let actual = redact(&actual, config);
+    let io_error = |error| JsonDiffError::SnapshotIo(path.to_path_buf(), error);
+
+    let text = match fs::read(path) {
+        Ok(text) if !update => text,
+        Ok(_) => return write(&actual, path).map_err(io_error),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return write(&actual, path).map_err(io_error)
+        }
+        Err(error) => return Err(io_error(error)),
+    };
+
+    let expected = serde_json::from_slice(&text)
+        .map_err(|error| JsonDiffError::RhsParse(ParseError::new(error, &text)))?;
+
+    compare(&actual, &expected, config, None).map_err(|error| match error {
+        JsonDiffError::Mismatch(mismatch) => JsonDiffError::Mismatch(mismatch.with_note(&format!(
+            "snapshot: {}\nrun with {}=1 to update it",
+            path.display(),
+            UPDATE_VAR
+        ))),
+        other => other,
+    })
+}
+
+fn write(value: &serde_json::Value, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut text = serde_json::to_string_pretty(value).map_err(io::Error::from)?;
+    text.push('\n');
+    fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CompareMode, NumericMode};
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("assert-json-diff-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_creates_missing_snapshots() {
+        let path = snapshot_path("created/user.json");
+        let config = Config::new(CompareMode::Strict);
+
+        check(&json!({ "id": 1 }), &path, &config, false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\n  \"id\": 1\n}\n");
+
+        check(&json!({ "id": 1 }), &path, &config, false).unwrap();
+        let error = check(&json!({ "id": 2 }), &path, &config, false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                r#"json atoms at path ".id" are not equal:
+    lhs:
+        2
+    rhs:
+        1
+
+snapshot: {}
+run with UPDATE_JSON_SNAPSHOTS=1 to update it"#,
+                path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_updates_snapshots() {
+        let path = snapshot_path("updated.json");
+        let config = Config::new(CompareMode::Strict);
+
+        check(&json!([1]), &path, &config, false).unwrap();
+        check(&json!([2]), &path, &config, true).unwrap();
+        check(&json!([2]), &path, &config, false).unwrap();
+        assert!(check(&json!([1]), &path, &config, false).is_err());
+    }
+
+    #[test]
+    fn test_honors_the_config() {
+        let path = snapshot_path("config.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, r#"{ "id": 1, "score": 1.0 }"#).unwrap();
+
+        let config = Config::new(CompareMode::Inclusive)
+            .numeric_mode(NumericMode::AssumeFloat)
+            .ignore_path(".id");
+        let actual = json!({ "id": 2, "score": 1, "extra": true });
+        check(&actual, &path, &config, false).unwrap();
+    }
+
+    #[test]
+    fn test_snapshots_are_redacted() {
+        let path = snapshot_path("redacted.json");
+        let config = Config::new(CompareMode::Strict).redact_path(".token", "token");
+
+        check(&json!({ "token": "abc" }), &path, &config, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"token\": \"[redacted:token]\"\n}\n"
+        );
+        check(&json!({ "token": "def" }), &path, &config, false).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_snapshots() {
+        let path = snapshot_path("invalid.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "{ \"id\": }").unwrap();
+
+        let error = check(&json!({}), &path, &Config::new(CompareMode::Strict), false).unwrap_err();
+        assert!(matches!(error, JsonDiffError::RhsParse(_)));
+    }
+}
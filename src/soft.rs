@@ -0,0 +1,165 @@
+use crate::core_ext::Indent;
+use crate::{assert_json_matches_no_panic, CompareMode, Config, JsonDiffError};
+use serde::Serialize;
+
+/// Collects the results of many JSON comparisons and reports all the failures at once.
+///
+/// Each comparison is given a label, such as the name of the endpoint whose response is being
+/// checked, and failures are grouped by label in the report. Call [`JsonAssertions::finish`] to
+/// panic if anything failed. If you forget, it happens when the collector is dropped.
+///
+/// ```should_panic
+/// use assert_json_diff::JsonAssertions;
+/// use serde_json::json;
+///
+/// let mut assertions = JsonAssertions::new();
+///
+/// assertions
+///     .include("GET /users/1", &json!({ "id": 1, "name": "Bob" }), &json!({ "id": 1 }))
+///     .include("GET /users/2", &json!({ "id": 2, "name": "Alice" }), &json!({ "id": 3 }))
+///     .check_eq("GET /health", &json!({ "ok": false }), &json!({ "ok": true }));
+///
+/// assertions.finish();
+/// ```
+///
+/// This will panic with the error message:
+///
+/// ```text
+/// 2 of 3 JSON assertions failed
+///
+/// GET /users/2:
+///     json atoms at path ".id" are not equal:
+///         expected:
+///             3
+///         actual:
+///             2
+///
+/// GET /health:
+///     json atoms at path ".ok" are not equal:
+///         lhs:
+///             false
+///         rhs:
+///             true
+/// ```
+#[derive(Debug, Default)]
+pub struct JsonAssertions {
+    count: usize,
+    failures: Vec<(String, Vec<JsonDiffError>)>,
+}
+
+impl JsonAssertions {
+    /// Create an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check that `actual` includes `expected`, like
+    /// [`assert_json_include`](macro.assert_json_include.html).
+    pub fn include<Actual, Expected>(
+        &mut self,
+        label: &str,
+        actual: &Actual,
+        expected: &Expected,
+    ) -> &mut Self
+    where
+        Actual: Serialize,
+        Expected: Serialize,
+    {
+        self.matches(label, actual, expected, Config::new(CompareMode::Inclusive))
+    }
+
+    /// Check that `lhs` and `rhs` are exactly equal, like
+    /// [`assert_json_eq`](macro.assert_json_eq.html).
+    pub fn check_eq<Lhs, Rhs>(&mut self, label: &str, lhs: &Lhs, rhs: &Rhs) -> &mut Self
+    where
+        Lhs: Serialize,
+        Rhs: Serialize,
+    {
+        self.matches(label, lhs, rhs, Config::new(CompareMode::Strict))
+    }
+
+    /// Compare `lhs` and `rhs` according to a configuration, like
+    /// [`assert_json_matches`](macro.assert_json_matches.html).
+    pub fn matches<Lhs, Rhs>(
+        &mut self,
+        label: &str,
+        lhs: &Lhs,
+        rhs: &Rhs,
+        config: Config,
+    ) -> &mut Self
+    where
+        Lhs: Serialize,
+        Rhs: Serialize,
+    {
+        let result = assert_json_matches_no_panic(lhs, rhs, config);
+        self.push(label, result)
+    }
+
+    /// Record the result of a comparison made some other way, such as with
+    /// [`assert_json_merge_patch_no_panic`](crate::assert_json_merge_patch_no_panic).
+    pub fn push(&mut self, label: &str, result: Result<(), JsonDiffError>) -> &mut Self {
+        self.count += 1;
+
+        if let Err(error) = result {
+            match self.failures.iter_mut().find(|(l, _)| l == label) {
+                Some((_, errors)) => errors.push(error),
+                None => self.failures.push((label.to_string(), vec![error])),
+            }
+        }
+        self
+    }
+
+    /// The number of comparisons that failed so far.
+    pub fn failure_count(&self) -> usize {
+        self.failures.iter().map(|(_, errors)| errors.len()).sum()
+    }
+
+    /// The combined report of all the failures, or `None` if every comparison passed.
+    pub fn report(&self) -> Option<String> {
+        if self.failures.is_empty() {
+            return None;
+        }
+
+        let groups = self
+            .failures
+            .iter()
+            .map(|(label, errors)| {
+                let errors = errors
+                    .iter()
+                    .map(|error| error.indent(4))
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                format!("{}:\n{}", label, errors)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        Some(format!(
+            "{} of {} JSON assertions failed\n\n{}",
+            self.failure_count(),
+            self.count,
+            groups
+        ))
+    }
+
+    /// Panic with the combined report if any comparison failed.
+    pub fn finish(mut self) {
+        let report = self.report();
+        self.failures.clear();
+        if let Some(report) = report {
+            panic!("\n\n{}\n\n", report);
+        }
+    }
+}
+
+impl Drop for JsonAssertions {
+    fn drop(&mut self) {
+        // panicking while already panicking aborts, which would hide the original panic
+        if std::thread::panicking() {
+            return;
+        }
+        if let Some(report) = self.report() {
+            panic!("\n\n{}\n\n", report);
+        }
+    }
+}
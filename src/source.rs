@@ -0,0 +1,214 @@
+use crate::diff::Key;
+
+/// The line, starting from 1, where the value at `path` starts in the JSON `text`.
+///
+/// If there is no value at `path`, such as for values missing from the left hand side, the line of
+/// its closest ancestor is returned. `text` must be valid JSON.
+pub(crate) fn line_of(text: &[u8], path: &[Key]) -> usize {
+    let mut scanner = Scanner {
+        text,
+        pos: 0,
+        line: 1,
+    };
+    scanner.find(path)
+}
+
+struct Scanner<'a> {
+    text: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl Scanner<'_> {
+    fn find(&mut self, path: &[Key]) -> usize {
+        self.skip_whitespace();
+        let line = self.line;
+
+        let (key, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return line,
+        };
+
+        let found = match (key, self.peek()) {
+            (Key::Field(field), Some(b'{')) => self.find_field(field),
+            (Key::Idx(idx), Some(b'[')) => self.find_idx(*idx),
+            _ => false,
+        };
+        if found {
+            self.find(rest)
+        } else {
+            line
+        }
+    }
+
+    /// Move to the value of `field` in the object at the current position.
+    fn find_field(&mut self, field: &str) -> bool {
+        // serde_json keeps the last value if a field is repeated
+        let mut found = None;
+
+        self.pos += 1;
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                break;
+            }
+            let key = self.string();
+            self.skip_whitespace();
+            self.pos += 1; // `:`
+            self.skip_whitespace();
+            if key == field {
+                found = Some((self.pos, self.line));
+            }
+            self.skip_value();
+            self.skip_whitespace();
+            if self.peek() == Some(b',') {
+                self.pos += 1;
+            }
+        }
+
+        match found {
+            Some((pos, line)) => {
+                self.pos = pos;
+                self.line = line;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move to element `idx` of the array at the current position.
+    fn find_idx(&mut self, idx: usize) -> bool {
+        self.pos += 1;
+        for _ in 0..idx {
+            self.skip_whitespace();
+            if self.peek() == Some(b']') {
+                return false;
+            }
+            self.skip_value();
+            self.skip_whitespace();
+            if self.peek() == Some(b',') {
+                self.pos += 1;
+            }
+        }
+        self.skip_whitespace();
+        self.peek().is_some_and(|byte| byte != b']')
+    }
+
+    fn skip_value(&mut self) {
+        match self.peek() {
+            Some(b'"') => {
+                self.string();
+            }
+            Some(open @ b'{') | Some(open @ b'[') => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                self.pos += 1;
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(byte) if byte == close => {
+                            self.pos += 1;
+                            break;
+                        }
+                        Some(b',') | Some(b':') => self.pos += 1,
+                        Some(_) => self.skip_value(),
+                        None => break,
+                    }
+                }
+            }
+            _ => {
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b'}' | b']') || byte.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Read the string at the current position.
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        let mut escaped = false;
+        self.pos += 1;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'\\' if !escaped => escaped = true,
+                b'"' if !escaped => break,
+                _ => escaped = false,
+            }
+        }
+        serde_json::from_slice(&self.text[start..self.pos]).unwrap_or_default()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if !byte.is_ascii_whitespace() {
+                break;
+            }
+            if byte == b'\n' {
+                self.line += 1;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str, path: &[Key]) -> usize {
+        line_of(text.as_bytes(), path)
+    }
+
+    fn field(name: &str) -> Key {
+        Key::Field(name.to_string())
+    }
+
+    const TEXT: &str = r#"{
+  "id": 1,
+  "name": "Bob \"the\" builder, {not an object}",
+  "tags": [
+    "a",
+    [1, 2],
+    { "x": null }
+  ],
+  "address": {
+    "city": "Copenhagen"
+  }
+}"#;
+
+    #[test]
+    fn test_fields_and_indexes() {
+        assert_eq!(line(TEXT, &[]), 1);
+        assert_eq!(line(TEXT, &[field("id")]), 2);
+        assert_eq!(line(TEXT, &[field("name")]), 3);
+        assert_eq!(line(TEXT, &[field("tags")]), 4);
+        assert_eq!(line(TEXT, &[field("tags"), Key::Idx(0)]), 5);
+        assert_eq!(line(TEXT, &[field("tags"), Key::Idx(1), Key::Idx(1)]), 6);
+        assert_eq!(line(TEXT, &[field("tags"), Key::Idx(2), field("x")]), 7);
+        assert_eq!(line(TEXT, &[field("address"), field("city")]), 10);
+    }
+
+    #[test]
+    fn test_missing_values_use_the_closest_ancestor() {
+        assert_eq!(line(TEXT, &[field("missing")]), 1);
+        assert_eq!(line(TEXT, &[field("tags"), Key::Idx(3)]), 4);
+        assert_eq!(line(TEXT, &[field("address"), field("zip")]), 9);
+        assert_eq!(line(TEXT, &[field("id"), field("nested")]), 2);
+        assert_eq!(line(TEXT, &[field("tags"), field("x")]), 4);
+    }
+
+    #[test]
+    fn test_escaped_and_repeated_keys() {
+        let text = "{\n\"a\\\"b\": 1,\n\"c\": 2,\n\"c\": 3\n}";
+        assert_eq!(line(text, &[field("a\"b")]), 2);
+        assert_eq!(line(text, &[field("c")]), 4);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::diff::{Key, Path};
+use crate::YamlError;
+use serde_json::{Map, Number, Value};
+use std::str::Chars;
+use yaml_rust2::{
+    parser::{Event, Parser, Tag},
+    scanner::{Marker, TScalarStyle},
+    Yaml,
+};
+
+/// Converts YAML text into the same JSON values that are compared by the rest of the crate.
+///
+/// Plain scalars are resolved using the YAML 1.2 core schema, so `true`, `1`, `1.5`, and `null`
+/// become JSON booleans, numbers, and null, while quoted scalars are always strings. JSON is valid
+/// YAML, so JSON text can be converted too.
+///
+/// YAML that has no JSON equivalent is an error rather than being converted in a lossy way. That
+/// includes mapping keys that aren't strings, anchors and aliases, custom tags such as `!Ref`,
+/// `.inf` and `.nan`, and more than one document.
+///
+/// ```
+/// use assert_json_diff::yaml_to_json;
+/// use serde_json::json;
+///
+/// let value = yaml_to_json("
+/// name: web
+/// ports:
+///   - 80
+///   - '443'
+/// ").unwrap();
+///
+/// assert_eq!(value, json!({ "name": "web", "ports": [80, "443"] }));
+///
+/// let error = yaml_to_json("
+/// ports:
+///   80: http
+/// ").unwrap_err();
+///
+/// assert_eq!(
+///     error.to_string(),
+///     r#"keys must be strings to be converted to JSON, found 80 in the mapping at path ".ports" at line 3 column 3
+///
+///     3 |   80: http
+///       |   ^"#
+/// );
+/// ```
+pub fn yaml_to_json(text: &str) -> Result<Value, YamlError> {
+    let mut converter = Converter {
+        text,
+        parser: Parser::new_from_str(text),
+        path: Vec::new(),
+    };
+    converter.document()
+}
+
+struct Converter<'a> {
+    text: &'a str,
+    parser: Parser<Chars<'a>>,
+    path: Vec<Key>,
+}
+
+impl Converter<'_> {
+    fn document(&mut self) -> Result<Value, YamlError> {
+        let mut value = None;
+        loop {
+            let (event, mark) = self.next()?;
+            match event {
+                Event::StreamEnd => return Ok(value.unwrap_or(Value::Null)),
+                Event::StreamStart | Event::DocumentEnd => {}
+                Event::DocumentStart if value.is_none() => {}
+                _ if value.is_some() => {
+                    return Err(self.error(mark, "expected a single YAML document but found more"))
+                }
+                event => value = Some(self.value(event, mark)?),
+            }
+        }
+    }
+
+    fn value(&mut self, event: Event, mark: Marker) -> Result<Value, YamlError> {
+        match event {
+            Event::Alias(_) => Err(self.unsupported(mark, "an alias")),
+            Event::Scalar(_, _, anchor, _)
+            | Event::SequenceStart(anchor, _)
+            | Event::MappingStart(anchor, _)
+                if anchor != 0 =>
+            {
+                Err(self.unsupported(mark, "an anchor"))
+            }
+            Event::Scalar(value, style, _, tag) => self.scalar(value, style, tag, mark),
+            Event::SequenceStart(_, tag) => {
+                self.check_tag(tag.as_ref(), &["seq"], mark)?;
+                self.sequence()
+            }
+            Event::MappingStart(_, tag) => {
+                self.check_tag(tag.as_ref(), &["map"], mark)?;
+                self.mapping()
+            }
+            _ => Err(self.error(mark, "unexpected YAML event")),
+        }
+    }
+
+    fn sequence(&mut self) -> Result<Value, YamlError> {
+        let mut array = Vec::new();
+        loop {
+            let (event, mark) = self.next()?;
+            if event == Event::SequenceEnd {
+                return Ok(Value::Array(array));
+            }
+            self.path.push(Key::Idx(array.len()));
+            let item = self.value(event, mark)?;
+            self.path.pop();
+            array.push(item);
+        }
+    }
+
+    fn mapping(&mut self) -> Result<Value, YamlError> {
+        let mut object = Map::new();
+        loop {
+            let (event, mark) = self.next()?;
+            let key = match event {
+                Event::MappingEnd => return Ok(Value::Object(object)),
+                Event::Scalar(_, _, anchor, _) if anchor != 0 => {
+                    return Err(self.unsupported(mark, "an anchor"))
+                }
+                Event::Scalar(key, style, _, tag) => match self.scalar(key, style, tag, mark)? {
+                    Value::String(key) => key,
+                    other => return Err(self.non_string_key(mark, &other.to_string())),
+                },
+                Event::Alias(_) => return Err(self.unsupported(mark, "an alias")),
+                Event::SequenceStart(..) => return Err(self.non_string_key(mark, "a sequence")),
+                Event::MappingStart(..) => return Err(self.non_string_key(mark, "a mapping")),
+                _ => return Err(self.error(mark, "unexpected YAML event")),
+            };
+
+            let (event, mark) = self.next()?;
+            self.path.push(Key::Field(key));
+            let item = self.value(event, mark)?;
+            // serde_json keeps the last value if a key is repeated
+            if let Some(Key::Field(key)) = self.path.pop() {
+                object.insert(key, item);
+            }
+        }
+    }
+
+    fn scalar(
+        &self,
+        value: String,
+        style: TScalarStyle,
+        tag: Option<Tag>,
+        mark: Marker,
+    ) -> Result<Value, YamlError> {
+        let tag = self.check_tag(tag.as_ref(), &["str", "int", "float", "bool", "null"], mark)?;
+        if style != TScalarStyle::Plain || tag == Some("str") {
+            return Ok(Value::String(value));
+        }
+
+        match Yaml::from_str(&value) {
+            Yaml::Null => Ok(Value::Null),
+            Yaml::Boolean(boolean) => Ok(Value::Bool(boolean)),
+            Yaml::Integer(integer) => Ok(Value::from(integer)),
+            Yaml::Real(real) => {
+                // integers too large for an i64 are parsed as reals
+                if let Ok(integer) = real.parse::<u64>() {
+                    return Ok(Value::from(integer));
+                }
+                real.parse()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .map(Value::Number)
+                    .ok_or_else(|| {
+                        let message = format!(
+                            "{} at path \"{}\" can't be represented as a JSON number",
+                            real,
+                            self.current_path()
+                        );
+                        self.error(mark, &message)
+                    })
+            }
+            _ => Ok(Value::String(value)),
+        }
+    }
+
+    /// Check that `tag` is one of the standard YAML tags in `allowed`, returning its name.
+    fn check_tag<'t>(
+        &self,
+        tag: Option<&'t Tag>,
+        allowed: &[&str],
+        mark: Marker,
+    ) -> Result<Option<&'t str>, YamlError> {
+        let tag = match tag {
+            Some(tag) => tag,
+            None => return Ok(None),
+        };
+        if tag.handle == "tag:yaml.org,2002:" && allowed.contains(&tag.suffix.as_str()) {
+            return Ok(Some(&tag.suffix));
+        }
+
+        let name = if tag.handle == "tag:yaml.org,2002:" {
+            format!("!!{}", tag.suffix)
+        } else {
+            format!("{}{}", tag.handle, tag.suffix)
+        };
+        let message = format!(
+            "the tag {} at path \"{}\" can't be converted to JSON",
+            name,
+            self.current_path()
+        );
+        Err(self.error(mark, &message))
+    }
+
+    fn next(&mut self) -> Result<(Event, Marker), YamlError> {
+        self.parser
+            .next_token()
+            .map_err(|error| self.error(*error.marker(), error.info()))
+    }
+
+    fn unsupported(&self, mark: Marker, what: &str) -> YamlError {
+        let message = format!(
+            "anchors and aliases can't be converted to JSON, found {} at path \"{}\"",
+            what,
+            self.current_path()
+        );
+        self.error(mark, &message)
+    }
+
+    fn non_string_key(&self, mark: Marker, key: &str) -> YamlError {
+        let message = format!(
+            "keys must be strings to be converted to JSON, found {} in the mapping at path \"{}\"",
+            key,
+            self.current_path()
+        );
+        self.error(mark, &message)
+    }
+
+    fn current_path(&self) -> Path {
+        self.path
+            .iter()
+            .cloned()
+            .fold(Path::default(), |path, key| path.append(key))
+    }
+
+    fn error(&self, mark: Marker, message: &str) -> YamlError {
+        YamlError::new(message, mark.line(), mark.col() + 1, self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn error(text: &str) -> String {
+        let error = yaml_to_json(text).unwrap_err();
+        error.to_string().lines().next().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_scalars() {
+        let value = yaml_to_json(
+            r#"
+nothing: ~
+empty:
+bools: [true, False]
+ints: [1, -2, 0x1f, 18446744073709551615]
+floats: [1.5, 1e3]
+strings: [abc, "1", '', !!str true, 2021-01-23]
+block: |
+  line
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "nothing": null,
+                "empty": null,
+                "bools": [true, false],
+                "ints": [1, -2, 31, 18446744073709551615u64],
+                "floats": [1.5, 1000.0],
+                "strings": ["abc", "1", "", "true", "2021-01-23"],
+                "block": "line\n",
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_is_yaml() {
+        let text = r#"{ "a": [1, 2.5, "x", null], "b": { "c": false } }"#;
+        assert_eq!(
+            yaml_to_json(text).unwrap(),
+            serde_json::from_str::<Value>(text).unwrap()
+        );
+        assert_eq!(yaml_to_json("").unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_unsupported_yaml() {
+        assert_eq!(
+            error("a:\n  - { 1: x }"),
+            r#"keys must be strings to be converted to JSON, found 1 in the mapping at path ".a[0]" at line 2 column 7"#
+        );
+        assert_eq!(
+            error("? [a]\n: x"),
+            r#"keys must be strings to be converted to JSON, found a sequence in the mapping at path "(root)" at line 1 column 3"#
+        );
+        assert_eq!(
+            error("base: &base { a: 1 }\nother: *base"),
+            r#"anchors and aliases can't be converted to JSON, found an anchor at path ".base" at line 1 column 13"#
+        );
+        assert_eq!(
+            error("a: !Ref b"),
+            r#"the tag !Ref at path ".a" can't be converted to JSON at line 1 column 9"#
+        );
+        assert_eq!(
+            error("a: .inf"),
+            r#".inf at path ".a" can't be represented as a JSON number at line 1 column 4"#
+        );
+        assert_eq!(
+            error("a: 1\n---\nb: 2"),
+            "expected a single YAML document but found more at line 2 column 1"
+        );
+        assert_eq!(
+            error("a: [1"),
+            "while parsing a flow sequence, expected ',' or ']' at line 2 column 1"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+fn json_diff(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_json-diff"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("assert-json-diff-cli-{}", std::process::id()))
+        .join(name)
+}
+
+fn file(name: &str, contents: &str) -> String {
+    let path = temp_path(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn exit_code_and_text_output() {
+    let lhs = file("text-lhs.json", "{\n  \"id\": 1,\n  \"etag\": \"a\"\n}");
+    let rhs = file("text-rhs.json", r#"{ "id": 2, "etag": "b" }"#);
+
+    let output = json_diff(&[&lhs, &rhs], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        r#"json atoms at path ".etag" (line 3 of lhs) are not equal:
+    lhs:
+        "a"
+    rhs:
+        "b"
+
+json atoms at path ".id" (line 2 of lhs) are not equal:
+    lhs:
+        1
+    rhs:
+        2
+"#
+    );
+
+    let output = json_diff(
+        &["--ignore-path", ".id", "--ignore-path", ".etag", &lhs, &rhs],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn config_flags() {
+    let expected = file("flags-expected.json", r#"{ "tags": [2, 1], "score": 1 }"#);
+    let actual = r#"{ "tags": [1, 2, 3], "score": 1.0, "extra": true }"#;
+
+    let output = json_diff(&["-", &expected], actual);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = json_diff(
+        &[
+            "--inclusive",
+            "--array-order",
+            "ignore",
+            "--numeric-mode",
+            "assume-float",
+            "-",
+            &expected,
+        ],
+        actual,
+    );
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn json_and_patch_formats() {
+    let lhs = file("format-lhs.json", r#"{ "a": 1, "b": true }"#);
+    let rhs = file("format-rhs.json", r#"{ "a": 2 }"#);
+
+    let output = json_diff(&["--format", "json", &lhs, &rhs], "");
+    assert_eq!(output.status.code(), Some(1));
+    let differences: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        differences,
+        serde_json::json!([
+            { "path": ".a", "kind": "value_mismatch", "lhs": 1, "rhs": 2, "line": 1 },
+            { "path": ".b", "kind": "missing_from_rhs", "lhs": true, "rhs": null, "line": 1 },
+        ])
+    );
+
+    let output = json_diff(&["--format", "patch", &lhs, &rhs], "");
+    assert_eq!(output.status.code(), Some(1));
+    let patch: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        patch,
+        serde_json::json!([
+            { "op": "replace", "path": "/a", "value": 2 },
+            { "op": "remove", "path": "/b" },
+        ])
+    );
+
+    let output = json_diff(&["--format", "patch", &lhs, &lhs], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "[]\n");
+
+    for flag in [
+        &["--inclusive"][..],
+        &["--ignore-path", ".b"],
+        &["--numeric-mode", "assume-float"],
+        &["--array-order", "ignore"],
+        &["--placeholders"],
+    ] {
+        let args = [&["--format", "patch"], flag, &[lhs.as_str(), rhs.as_str()]].concat();
+        let output = json_diff(&args, "");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8(output.stderr).unwrap().contains(&format!(
+            "`--format patch` can't be used with `{}`",
+            flag[0]
+        )));
+    }
+}
+
+#[test]
+fn errors() {
+    let valid = file("errors-valid.json", "{}");
+
+    let output = json_diff(&["-", &valid], "{ \"a\": }");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with(
+        "json-diff: Couldn't parse left hand side as JSON: expected value at line 1 column 8"
+    ));
+
+    let output = json_diff(&[&valid, "does-not-exist.json"], "");
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = json_diff(&["--ignore-path", "id", &valid, &valid], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Invalid path pattern `id`"));
+
+    let output = json_diff(&["-", "-"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn invalid_tolerances() {
+    let lhs = file("tolerance-lhs.json", r#"{ "a": 1 }"#);
+    let rhs = file("tolerance-rhs.json", r#"{ "a": 2 }"#);
+
+    for mode in [
+        "absolute:nan",
+        "absolute:-1",
+        "relative:inf",
+        "relative:-0.5",
+    ] {
+        let output = json_diff(&["--numeric-mode", mode, &lhs, &rhs], "");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .contains(&format!("invalid tolerance in `{}`", mode)));
+    }
+
+    let output = json_diff(&["--numeric-mode", "absolute:0", &lhs, &lhs], "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn dir_subcommand() {
+    file("dir-lhs/users/1.json", r#"{ "id": 1, "name": "Bob" }"#);
+    file("dir-rhs/users/1.json", r#"{ "id": 1, "name": "Alice" }"#);
+    file("dir-lhs/extra.json", "{}");
+    let lhs_dir = temp_path("dir-lhs");
+    let rhs_dir = temp_path("dir-rhs");
+    let (lhs_dir, rhs_dir) = (lhs_dir.to_str().unwrap(), rhs_dir.to_str().unwrap());
+
+    let output = json_diff(&["dir", lhs_dir, rhs_dir], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!(
+            r#"2 of 2 JSON files don't match
+
+only in lhs:
+    extra.json
+
+{}:
+    json atoms at path ".name" (line 1 of lhs) are not equal:
+        lhs:
+            "Bob"
+        rhs:
+            "Alice"
+"#,
+            Path::new("users").join("1.json").display()
+        )
+    );
+
+    let output = json_diff(
+        &[
+            "dir",
+            "--format",
+            "json",
+            "--ignore-path",
+            ".name",
+            lhs_dir,
+            rhs_dir,
+        ],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report,
+        serde_json::json!({ "only_in_lhs": ["extra.json"], "only_in_rhs": [], "files": {} })
+    );
+
+    let output = json_diff(&["dir", lhs_dir, lhs_dir], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
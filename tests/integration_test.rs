@@ -1,9 +1,17 @@
 use assert_json_diff::{
     assert_json_eq, assert_json_include, assert_json_matches, assert_json_matches_no_panic,
-    CompareMode, Config, NumericMode,
+    assert_json_merge_patch, assert_json_merge_patch_no_panic, assert_json_snapshot,
+    assert_json_text_eq, assert_json_text_include, assert_json_text_matches_no_panic, diff_dirs,
+    diff_json, json_matches, ArraySortingMode, CompareMode, Config, DifferenceKind, JsonAssertions,
+    JsonDiffError, Key, Matcher, NumericMode,
 };
 use serde::Serialize;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    error::Error,
+    panic::{self, AssertUnwindSafe},
+};
 
 #[test]
 fn can_pass() {
@@ -65,6 +73,55 @@ fn different_numeric_types_assume_float() {
     assert_json_matches!(actual, expected, config.compare_mode(CompareMode::Strict))
 }
 
+#[test]
+fn numeric_tolerance() {
+    let actual = json!({ "average": 2.0 / 3.0, "ratio": 0.1 + 0.2, "count": 3 });
+    let expected = json!({ "average": 0.6667, "ratio": 0.3, "count": 3.0 });
+
+    assert_json_matches!(
+        actual,
+        expected,
+        Config::new(CompareMode::Inclusive).numeric_mode(NumericMode::AbsoluteTolerance(0.001)),
+    );
+    assert_json_matches!(
+        actual,
+        expected,
+        Config::new(CompareMode::Strict).numeric_mode(NumericMode::RelativeTolerance(0.001)),
+    );
+
+    let error = assert_json_matches_no_panic(
+        &actual,
+        &expected,
+        Config::new(CompareMode::Strict).numeric_mode(NumericMode::Ulps(4)),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains(r#"json numbers at path ".average" differ by"#));
+    assert!(!error.contains(".ratio"));
+}
+
+#[test]
+#[should_panic(expected = "Invalid tolerance `NaN`")]
+fn nan_tolerance() {
+    Config::new(CompareMode::Strict).numeric_mode(NumericMode::AbsoluteTolerance(f64::NAN));
+}
+
+#[test]
+fn invalid_tolerances() {
+    for mode in [
+        NumericMode::AbsoluteTolerance(-1.0),
+        NumericMode::AbsoluteTolerance(f64::INFINITY),
+        NumericMode::RelativeTolerance(f64::NAN),
+    ] {
+        assert!(Config::new(CompareMode::Strict)
+            .try_numeric_mode(mode)
+            .is_err());
+    }
+    assert!(Config::new(CompareMode::Strict)
+        .try_numeric_mode(NumericMode::AbsoluteTolerance(0.0))
+        .is_ok());
+}
+
 #[test]
 fn can_pass_with_exact_match() {
     assert_json_eq!(json!({ "a": { "b": true } }), json!({ "a": { "b": true } }));
@@ -111,6 +168,216 @@ fn exact_match_without_panicking() {
     .is_err());
 }
 
+#[test]
+fn arrays_ignoring_order() {
+    let config = Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Ignore);
+    assert_json_matches!(
+        json!({ "permissions": ["write", "admin", "read"] }),
+        json!({ "permissions": ["read", "write"] }),
+        config.clone(),
+    );
+
+    assert_json_matches!(
+        json!([{ "id": 3 }, { "id": 1 }, { "id": 2 }]),
+        json!([{ "id": 1 }, { "id": 2 }, { "id": 3 }]),
+        config.compare_mode(CompareMode::Strict),
+    );
+}
+
+#[test]
+#[should_panic]
+fn arrays_ignoring_order_strict_should_fail_with_extra_elements() {
+    assert_json_matches!(
+        json!(["read", "write", "admin"]),
+        json!(["write", "read"]),
+        Config::new(CompareMode::Strict).array_sorting_mode(ArraySortingMode::Ignore),
+    );
+}
+
+#[test]
+fn inclusive_array_contains() {
+    let actual = json!({
+        "items": [
+            { "id": 1, "name": "a" },
+            { "id": 2, "name": "b" },
+            { "id": 3, "name": "c" },
+            { "id": 5, "name": "e" },
+        ]
+    });
+
+    assert_json_matches!(
+        actual,
+        json!({ "items": [{ "id": 5 }, { "id": 2 }] }),
+        Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Ignore),
+    );
+
+    assert_json_matches!(
+        actual,
+        json!({ "items": [{ "id": 2 }, { "id": 5 }] }),
+        Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Subsequence),
+    );
+
+    assert!(assert_json_matches_no_panic(
+        &actual,
+        &json!({ "items": [{ "id": 5 }, { "id": 2 }] }),
+        Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Subsequence),
+    )
+    .is_err());
+}
+
+#[test]
+fn ignored_paths() {
+    let actual = json!({
+        "id": "2ba0c0cb",
+        "created_at": "2021-01-23T12:00:00Z",
+        "data": { "users": [{ "id": 1, "etag": "x" }, { "id": 2, "etag": "y" }] },
+    });
+
+    assert_json_matches!(
+        actual,
+        json!({
+            "id": "ignored",
+            "data": { "users": [{ "id": 1 }, { "id": 2, "etag": "z" }] },
+        }),
+        Config::new(CompareMode::Strict)
+            .ignore_path(".id")
+            .ignore_path(".created_at")
+            .ignore_path(".data.users[*].etag"),
+    );
+
+    let error = assert_json_matches_no_panic(
+        &actual,
+        &json!({ "id": "ignored", "data": { "users": [{ "id": 3 }] } }),
+        Config::new(CompareMode::Inclusive).ignore_path("..etag"),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains(".data.users[0].id"));
+    assert!(!error.contains("etag"));
+}
+
+#[test]
+#[should_panic(expected = "Invalid path pattern `data.id`")]
+fn invalid_ignored_path() {
+    Config::new(CompareMode::Strict).ignore_path("data.id");
+}
+
+#[test]
+fn placeholders() {
+    let actual = json!({
+        "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        "created": "2021-01-23T12:00:00.123Z",
+        "count": 3,
+        "users": [{ "name": "Bob", "admin": false }],
+    });
+
+    assert_json_matches!(
+        actual,
+        json!({
+            "id": "${any_uuid}",
+            "users": [{ "name": "${regex:^[A-Z][a-z]+$}", "admin": "${any_bool}" }],
+        }),
+        Config::new(CompareMode::Inclusive).enable_placeholders(),
+    );
+
+    assert_json_matches!(
+        actual,
+        json!({
+            "id": "${any_uuid}",
+            "created": "${iso8601}",
+            "count": "${any_number}",
+            "users": "${non_empty}",
+        }),
+        Config::new(CompareMode::Strict).enable_placeholders(),
+    );
+
+    let error = assert_json_matches_no_panic(
+        &actual,
+        &json!({ "created": "${any_number}", "count": "${unknown}" }),
+        Config::new(CompareMode::Inclusive).enable_placeholders(),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains(r#"rejected by matcher "${any_number}": expected a number"#));
+    assert!(error.contains(r#"rejected by matcher "${unknown}": unknown placeholder `unknown`"#));
+}
+
+#[derive(Debug)]
+struct SignedUrl {
+    host: &'static str,
+}
+
+impl Matcher for SignedUrl {
+    fn matches(&self, actual: &Value) -> Result<(), String> {
+        let url = actual.as_str().ok_or("expected a string")?;
+        if !url.starts_with(&format!("https://{}/", self.host)) {
+            return Err(format!("expected a URL on {}", self.host));
+        }
+        if !url.contains("signature=") {
+            return Err("the URL isn't signed".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_matchers() {
+    let config = Config::new(CompareMode::Inclusive).matcher_at(
+        "..download_url",
+        SignedUrl {
+            host: "cdn.example.com",
+        },
+    );
+
+    assert_json_matches!(
+        json!({ "files": [{ "download_url": "https://cdn.example.com/a?signature=abc" }] }),
+        json!({ "files": [{ "download_url": null }] }),
+        config.clone(),
+    );
+
+    let error = assert_json_matches_no_panic(
+        &json!({ "files": [{ "download_url": "https://cdn.example.com/a" }] }),
+        &json!({ "files": [{ "download_url": null }] }),
+        config,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains(
+        r#"json atom at path ".files[0].download_url" was rejected by matcher "..download_url": the URL isn't signed"#
+    ));
+}
+
+#[test]
+fn structured_differences() {
+    let actual = json!({ "users": [{ "id": 1, "name": "bob" }] });
+    let expected = json!({ "users": [{ "id": "1", "name": "alice" }], "total": 1 });
+
+    let diffs = diff_json(&actual, &expected, &Config::new(CompareMode::Inclusive));
+    let mut summary = diffs
+        .iter()
+        .map(|d| (d.path().to_string(), d.kind().clone()))
+        .collect::<Vec<_>>();
+    summary.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(
+        summary,
+        vec![
+            (".total".to_string(), DifferenceKind::MissingFromLhs),
+            (".users[0].id".to_string(), DifferenceKind::TypeMismatch),
+            (".users[0].name".to_string(), DifferenceKind::ValueMismatch),
+        ]
+    );
+
+    let name_diff = diffs
+        .iter()
+        .find(|d| d.path().keys().last() == Some(&Key::Field("name".to_string())))
+        .unwrap();
+    assert_eq!(name_diff.lhs(), Some(&json!("bob")));
+    assert_eq!(name_diff.rhs(), Some(&json!("alice")));
+
+    assert!(diff_json(&actual, &actual, &Config::new(CompareMode::Strict)).is_empty());
+}
+
 #[derive(Serialize)]
 struct User {
     id: i32,
@@ -151,6 +418,171 @@ fn include_with_serializable_ref() {
     );
 }
 
+#[test]
+fn serialization_errors_dont_panic() {
+    let mut unserializable = HashMap::new();
+    unserializable.insert((1, 2), 3);
+
+    let error = assert_json_matches_no_panic(
+        &unserializable,
+        &json!({}),
+        Config::new(CompareMode::Strict),
+    )
+    .unwrap_err();
+    assert!(matches!(error, JsonDiffError::LhsSerialization(_)));
+    assert!(error.source().is_some());
+    assert!(error
+        .to_string()
+        .starts_with("Couldn't convert left hand side value to JSON."));
+
+    let error = assert_json_matches_no_panic(
+        &json!({}),
+        &unserializable,
+        Config::new(CompareMode::Strict),
+    )
+    .unwrap_err();
+    assert!(matches!(error, JsonDiffError::RhsSerialization(_)));
+}
+
+#[test]
+fn errors_can_be_propagated() {
+    fn check(actual: Value) -> Result<(), Box<dyn Error>> {
+        assert_json_matches_no_panic(
+            &actual,
+            &json!({ "a": 1 }),
+            Config::new(CompareMode::Inclusive),
+        )?;
+        Ok(())
+    }
+
+    assert!(check(json!({ "a": 1, "b": 2 })).is_ok());
+
+    let error = check(json!({ "a": 2 })).unwrap_err();
+    let mismatch = match error.downcast_ref::<JsonDiffError>() {
+        Some(JsonDiffError::Mismatch(mismatch)) => mismatch,
+        other => panic!("unexpected error: {:?}", other),
+    };
+    assert_eq!(mismatch.differences().len(), 1);
+    assert_eq!(mismatch.differences()[0].path().to_string(), ".a");
+    assert_eq!(
+        error.to_string(),
+        r#"json atoms at path ".a" are not equal:
+    expected:
+        1
+    actual:
+        2"#
+    );
+}
+
+#[test]
+fn soft_assertions() {
+    let mut assertions = JsonAssertions::new();
+    assertions
+        .include(
+            "user",
+            &json!({ "id": 1, "name": "Bob" }),
+            &json!({ "id": 1 }),
+        )
+        .check_eq("health", &json!({ "ok": true }), &json!({ "ok": true }));
+    assert_eq!(assertions.failure_count(), 0);
+    assert_eq!(assertions.report(), None);
+    assertions.finish();
+
+    let mut assertions = JsonAssertions::new();
+    assertions
+        .include("user", &json!({ "id": 1 }), &json!({ "id": 2 }))
+        .check_eq("health", &json!({ "ok": true }), &json!({ "ok": true }))
+        .matches(
+            "user",
+            &json!([1, 2]),
+            &json!([2, 1, 3]),
+            Config::new(CompareMode::Inclusive).array_sorting_mode(ArraySortingMode::Ignore),
+        )
+        .push(
+            "patch",
+            assert_json_merge_patch_no_panic(&json!({}), &json!({ "a": 1 }), &json!({})),
+        );
+
+    assert_eq!(assertions.failure_count(), 3);
+    assert_eq!(
+        assertions.report().unwrap(),
+        r#"3 of 4 JSON assertions failed
+
+user:
+    json atoms at path ".id" are not equal:
+        expected:
+            2
+        actual:
+            1
+
+    json atom at path "[2]" is missing from actual:
+        expected:
+            3
+
+patch:
+    json atom at path ".a" is missing from rhs:
+        lhs:
+            1"#
+    );
+
+    let result = panic::catch_unwind(AssertUnwindSafe(move || assertions.finish()));
+    let message = result.unwrap_err();
+    assert!(message
+        .downcast_ref::<String>()
+        .unwrap()
+        .contains("3 of 4 JSON assertions failed"));
+}
+
+#[test]
+#[should_panic(expected = "1 of 1 JSON assertions failed")]
+fn soft_assertions_panic_when_dropped() {
+    let mut assertions = JsonAssertions::new();
+    assertions.check_eq("value", &json!(1), &json!(2));
+}
+
+#[test]
+fn merge_patches() {
+    assert_json_merge_patch!(
+        base: json!({ "id": 1, "profile": { "name": "Bob", "age": 30 }, "tags": ["a"] }),
+        patch: json!({ "profile": { "age": null }, "tags": ["b", "c"] }),
+        expected: json!({ "id": 1, "profile": { "name": "Bob" }, "tags": ["b", "c"] }),
+    );
+
+    let user = User {
+        id: 1,
+        username: "bob".to_string(),
+    };
+    assert_json_merge_patch!(
+        base: user,
+        patch: json!({ "username": "alice" }),
+        expected: json!({ "id": 1, "username": "alice" }),
+    );
+
+    let error = assert_json_merge_patch_no_panic(
+        &json!({ "a": 1, "b": 2 }),
+        &json!({ "b": null }),
+        &json!({ "a": 1, "b": 2 }),
+    )
+    .unwrap_err()
+    .to_string();
+    assert_eq!(
+        error,
+        r#"json atom at path ".b" is missing from lhs:
+    rhs:
+        2"#
+    );
+}
+
+#[test]
+#[should_panic]
+fn merge_patch_can_fail() {
+    assert_json_merge_patch!(
+        base: json!({ "a": 1 }),
+        patch: json!({ "a": 2 }),
+        expected: json!({ "a": 1 }),
+    );
+}
+
 #[test]
 fn eq_with_serializable() {
     let user = User {
@@ -182,3 +614,286 @@ fn eq_with_serializable_ref() {
         &user,
     );
 }
+
+#[test]
+fn json_matches_uses_the_config() {
+    let actual = json!({ "id": 1, "tags": ["b", "a"], "score": 1.0 });
+    let expected = json!({ "tags": ["a", "b"], "score": 1 });
+
+    assert!(!json_matches(
+        &actual,
+        &expected,
+        &Config::new(CompareMode::Inclusive)
+    ));
+    assert!(json_matches(
+        &actual,
+        &expected,
+        &Config::new(CompareMode::Inclusive)
+            .array_sorting_mode(ArraySortingMode::Ignore)
+            .numeric_mode(NumericMode::AssumeFloat)
+    ));
+    assert!(!json_matches(
+        &actual,
+        &expected,
+        &Config::new(CompareMode::Strict)
+            .array_sorting_mode(ArraySortingMode::Ignore)
+            .numeric_mode(NumericMode::AssumeFloat)
+    ));
+}
+
+#[test]
+fn guided_serialization() {
+    #[derive(Serialize)]
+    struct Response {
+        id: u32,
+        items: Vec<u32>,
+        cache: HashMap<(u32, u32), u32>,
+    }
+
+    let mut cache = HashMap::new();
+    cache.insert((1, 2), 3);
+    let response = Response {
+        id: 1,
+        items: vec![1, 2, 3],
+        cache,
+    };
+
+    // `cache` can't be serialized, but isn't mentioned in the expected value
+    let config = Config::new(CompareMode::Inclusive).guided_serialization();
+    assert_json_matches!(
+        response,
+        json!({ "id": 1, "items": [1, 2] }),
+        config.clone()
+    );
+
+    let error = assert_json_matches_no_panic(&response, &json!({ "id": 2 }), config).unwrap_err();
+    let expected = assert_json_matches_no_panic(
+        &json!({ "id": 1 }),
+        &json!({ "id": 2 }),
+        Config::new(CompareMode::Inclusive),
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), expected.to_string());
+
+    let error = assert_json_matches_no_panic(
+        &response,
+        &json!({ "id": 1 }),
+        Config::new(CompareMode::Inclusive),
+    )
+    .unwrap_err();
+    assert!(matches!(error, JsonDiffError::LhsSerialization(_)));
+}
+
+#[test]
+fn json_text() {
+    let body = "{\n  \"id\": 1,\n  \"tags\": [\"a\", \"b\"]\n}";
+
+    assert_json_text_include!(actual: body, expected: r#"{ "tags": ["a"] }"#);
+    assert_json_text_eq!(
+        body.as_bytes(),
+        br#"{ "tags": ["a", "b"], "id": 1 }"#.to_vec()
+    );
+
+    let error = assert_json_text_matches_no_panic(
+        body,
+        r#"{ "id": 1, "tags": ["a", "c"], "name": "Bob" }"#,
+        Config::new(CompareMode::Strict),
+    )
+    .unwrap_err();
+    let lines = match &error {
+        JsonDiffError::Mismatch(mismatch) => mismatch
+            .differences()
+            .iter()
+            .map(|d| (d.path().to_string(), d.line()))
+            .collect::<Vec<_>>(),
+        other => panic!("unexpected error: {}", other),
+    };
+    assert_eq!(
+        lines,
+        vec![
+            (".tags[1]".to_string(), Some(3)),
+            (".name".to_string(), Some(1)),
+        ]
+    );
+    assert!(error
+        .to_string()
+        .starts_with(r#"json atoms at path ".tags[1]" (line 3 of lhs) are not equal:"#));
+}
+
+#[test]
+fn invalid_json_text() {
+    let error = assert_json_text_matches_no_panic(
+        "{\n  \"a\": 1\n  \"b\": 2\n}",
+        "{}",
+        Config::new(CompareMode::Inclusive),
+    )
+    .unwrap_err();
+
+    match &error {
+        JsonDiffError::LhsParse(err) => assert_eq!((err.line(), err.column()), (3, 3)),
+        other => panic!("unexpected error: {}", other),
+    }
+    assert_eq!(
+        error.to_string(),
+        r#"Couldn't parse left hand side as JSON: expected `,` or `}` at line 3 column 3
+
+    3 |   "b": 2
+      |   ^"#
+    );
+
+    let error = assert_json_text_matches_no_panic("{}", "[1,", Config::new(CompareMode::Inclusive))
+        .unwrap_err();
+    assert!(matches!(error, JsonDiffError::RhsParse(_)));
+}
+
+#[test]
+fn snapshots() {
+    let path = std::env::temp_dir()
+        .join(format!("assert-json-diff-test-{}", std::process::id()))
+        .join("snapshot.json");
+    let _ = std::fs::remove_file(&path);
+
+    assert_json_snapshot!(json!({ "id": 1, "tags": ["a"] }), &path);
+    assert_json_snapshot!(json!({ "id": 1, "tags": ["a"] }), &path);
+    assert_json_snapshot!(
+        json!({ "id": 1, "tags": ["a"], "extra": true }),
+        &path,
+        Config::new(CompareMode::Inclusive),
+    );
+
+    let result = panic::catch_unwind(|| {
+        assert_json_snapshot!(json!({ "id": 2, "tags": ["a"] }), &path);
+    });
+    let message = result.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains(r#"json atoms at path ".id" are not equal:"#));
+    assert!(message.contains("run with UPDATE_JSON_SNAPSHOTS=1 to update it"));
+}
+
+#[test]
+fn directories() {
+    let root = std::env::temp_dir().join(format!("assert-json-diff-dirs-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for (path, contents) in [
+        (
+            "actual/users/1.json",
+            r#"{ "id": 1, "name": "Bob", "etag": "a" }"#,
+        ),
+        ("actual/users/2.json", r#"{ "id": 2 }"#),
+        ("expected/users/1.json", r#"{ "id": 1, "name": "Alice" }"#),
+        ("expected/users/3.json", r#"{ "id": 3 }"#),
+    ] {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    let report = diff_dirs(
+        root.join("actual"),
+        root.join("expected"),
+        Config::new(CompareMode::Inclusive),
+    )
+    .unwrap();
+
+    assert!(!report.is_match());
+    assert_eq!(report.compared(), 1);
+    assert_eq!(
+        report.only_in_lhs(),
+        &["users/2.json".into()] as &[std::path::PathBuf]
+    );
+    assert_eq!(
+        report.only_in_rhs(),
+        &["users/3.json".into()] as &[std::path::PathBuf]
+    );
+
+    let differences = report
+        .differences()
+        .map(|(file, difference)| (file.to_str().unwrap(), difference.path().to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(differences, vec![("users/1.json", ".name".to_string())]);
+    assert!(report.to_string().starts_with(
+        "3 of 3 JSON files don't match\n\nonly in actual:\n    users/2.json\n\nonly in expected:\n    users/3.json"
+    ));
+}
+
+#[test]
+fn redacted_values_arent_printed() {
+    let config = Config::new(CompareMode::Inclusive)
+        .redact_path(".users[*].api_key", "api_key")
+        .redact_values("email", |value: &Value| {
+            value.as_str().is_some_and(|s| s.contains('@'))
+        })
+        .context(1);
+
+    let actual = json!({
+        "users": [{ "name": "Bob", "api_key": "sk_live_123", "email": "bob@example.com" }],
+    });
+    let expected = json!({
+        "users": [{ "name": "Alice", "api_key": "[redacted:api_key]" }],
+    });
+
+    let message = assert_json_matches_no_panic(&actual, &expected, config.clone())
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains(r#""api_key": "[redacted:api_key]""#));
+    assert!(message.contains(r#""email": "[redacted:email]""#));
+    assert!(!message.contains("sk_live_123"));
+    assert!(!message.contains("bob@example.com"));
+
+    let diffs = diff_json(&actual, &expected, &config);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].path().to_string(), ".users[0].name");
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml() {
+    use assert_json_diff::{assert_yaml_eq, assert_yaml_include, assert_yaml_matches_no_panic};
+
+    let manifest = "
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+  labels: { app: web }
+spec:
+  ports:
+    - port: 80
+      targetPort: 8080
+";
+    assert_yaml_include!(actual: manifest, expected: "spec: { ports: [{ port: 80 }] }");
+    assert_yaml_eq!("a: [1, 2]", r#"{ "a": [1, 2] }"#);
+
+    let error = assert_yaml_matches_no_panic(
+        manifest,
+        "spec:\n  ports:\n    - port: '80'",
+        Config::new(CompareMode::Inclusive),
+    )
+    .unwrap_err();
+    match &error {
+        JsonDiffError::Mismatch(mismatch) => {
+            let diff = &mismatch.differences()[0];
+            assert_eq!(diff.path().to_string(), ".spec.ports[0].port");
+            assert_eq!(diff.kind(), &DifferenceKind::TypeMismatch);
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let error = assert_yaml_matches_no_panic(
+        manifest,
+        "base: &base { a: 1 }",
+        Config::new(CompareMode::Strict),
+    )
+    .unwrap_err();
+    assert!(matches!(error, JsonDiffError::RhsYaml(_)));
+    assert!(error
+        .to_string()
+        .starts_with("Couldn't parse right hand side as YAML: anchors and aliases"));
+
+    let result = panic::catch_unwind(|| {
+        assert_yaml_eq!("a: 1", "a: 2");
+    });
+    let message = result.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains(r#"json atoms at path ".a" are not equal:"#));
+}